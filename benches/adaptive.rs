@@ -0,0 +1,44 @@
+//! Shows the effect of the sorted / reversed pre-scan on presorted inputs and
+//! of natural run detection on nearly sorted ones.
+//!
+//! Run with `cargo bench --bench adaptive`.
+
+#[allow(dead_code)]
+#[path = "../src/datagen.rs"]
+mod datagen;
+
+use std::time::Instant;
+
+const N: usize = 10_000_000;
+
+fn main() {
+    let random = datagen::random(N, 1);
+    let sorted = datagen::sorted(N);
+    let reversed = datagen::reversed(N);
+    let nearly_sorted = datagen::nearly_sorted(N, 1);
+
+    for (name, data) in [
+        ("sorted", sorted),
+        ("reversed", reversed),
+        ("nearly", nearly_sorted),
+        ("random", random),
+    ] {
+        let mut wave = data.clone();
+        let start = Instant::now();
+        wsort::sort_unstable(&mut wave);
+        let dur_wave = start.elapsed();
+
+        let mut std = data;
+        let start = Instant::now();
+        std.sort_unstable();
+        let dur_std = start.elapsed();
+
+        assert_eq!(wave, std);
+        println!(
+            "{:<8}  WaveSort: {:.6} s  Std: {:.6} s",
+            name,
+            dur_wave.as_secs_f64(),
+            dur_std.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! Inputs with few distinct keys, where the three-way partition lets
+//! `downwave` drop every key equal to the pivot from further merging.
+//!
+//! Each cardinality is sorted with every `Partition` strategy, so the
+//! `auto` column should track the faster of `two-way` and `three-way`.
+//!
+//! Run with `cargo bench --bench duplicates`.
+
+use std::time::Instant;
+
+use wsort::{Partition, SortConfig};
+
+const N: usize = 10_000_000;
+
+fn main() {
+    for distinct in [2, 100, 10_000, N as u64] {
+        let mut data = Vec::with_capacity(N);
+        let mut seed: u64 = 1;
+        for _ in 0..N {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            data.push(((seed >> 33) % distinct) as i32);
+        }
+
+        let mut std = data.clone();
+        let start = Instant::now();
+        std.sort_unstable();
+        let dur_std = start.elapsed();
+
+        print!("{distinct:>8} distinct");
+        for (name, partition) in [
+            ("auto", Partition::Auto),
+            ("two-way", Partition::TwoWay),
+            ("three-way", Partition::ThreeWay),
+        ] {
+            let cfg = SortConfig {
+                partition,
+                ..SortConfig::default()
+            };
+            let mut wave = data.clone();
+            let start = Instant::now();
+            wsort::sort_with_config(&mut wave, &cfg);
+            let dur_wave = start.elapsed();
+            assert_eq!(wave, std);
+            print!("  {name}: {:.6} s", dur_wave.as_secs_f64());
+        }
+        println!("  Std: {:.6} s", dur_std.as_secs_f64());
+    }
+}
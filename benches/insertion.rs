@@ -0,0 +1,48 @@
+//! Nearly sorted inputs, where `upwave` finishes its runs with the binary
+//! insertion sort: `i32`s with one element in a thousand displaced, and the
+//! same shape with strings, whose comparisons cost more than a shift.
+//!
+//! Run with `cargo bench --bench insertion`.
+
+use std::time::Instant;
+
+const N: usize = 4_000_000;
+
+/// Sorted `0..n` with every `stride`-th element swapped far away.
+fn nearly_sorted(n: usize, stride: usize) -> Vec<i32> {
+    let mut v: Vec<i32> = (0..n as i32).collect();
+    for i in (0..n).step_by(stride) {
+        v.swap(i, (i * 7919) % n);
+    }
+    v
+}
+
+fn bench<T: Ord + Clone>(name: &str, data: Vec<T>) {
+    let mut wave = data.clone();
+    let start = Instant::now();
+    wsort::sort_unstable(&mut wave);
+    let dur_wave = start.elapsed();
+
+    let mut std = data;
+    let start = Instant::now();
+    std.sort_unstable();
+    let dur_std = start.elapsed();
+
+    assert!(wave == std);
+    println!(
+        "{name:<16} wsort: {:.6} s  std: {:.6} s",
+        dur_wave.as_secs_f64(),
+        dur_std.as_secs_f64()
+    );
+}
+
+fn main() {
+    for stride in [1_000, 100] {
+        bench(&format!("i32 1/{stride}"), nearly_sorted(N, stride));
+        let strings = nearly_sorted(N / 8, stride)
+            .into_iter()
+            .map(|v| format!("key-{v:010}"))
+            .collect();
+        bench(&format!("strings 1/{stride}"), strings);
+    }
+}
@@ -0,0 +1,43 @@
+//! `sort_parallel` on the rayon pool against the sequential WaveSort and
+//! std, on 10M random `i32`s. The speedup tracks the number of cores rayon
+//! finds; set `RAYON_NUM_THREADS` to vary it.
+//!
+//! Run with `cargo bench --features rayon --bench parallel`.
+
+use std::time::Instant;
+
+#[allow(dead_code)]
+#[path = "../src/datagen.rs"]
+mod datagen;
+
+const N: usize = 10_000_000;
+
+fn main() {
+    let data = datagen::random(N, 1);
+
+    let mut wave = data.clone();
+    let start = Instant::now();
+    wsort::sort_unstable(&mut wave);
+    let dur_wave = start.elapsed();
+
+    let mut parallel = data.clone();
+    let start = Instant::now();
+    wsort::sort_parallel(&mut parallel);
+    let dur_parallel = start.elapsed();
+
+    let mut std = data;
+    let start = Instant::now();
+    std.sort_unstable();
+    let dur_std = start.elapsed();
+
+    assert_eq!(wave, std);
+    assert_eq!(parallel, std);
+    println!(
+        "{} threads  wsort: {:.6} s  parallel: {:.6} s ({:.2}x)  std: {:.6} s",
+        rayon::current_num_threads(),
+        dur_wave.as_secs_f64(),
+        dur_parallel.as_secs_f64(),
+        dur_wave.as_secs_f64() / dur_parallel.as_secs_f64(),
+        dur_std.as_secs_f64()
+    );
+}
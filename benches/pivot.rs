@@ -0,0 +1,53 @@
+//! Comparison counts of the pure Rust sort on inputs that stress pivot
+//! selection, including Musser's median-of-three killer.
+//!
+//! Run with `cargo bench --bench pivot`.
+
+#[allow(dead_code)]
+#[path = "../src/datagen.rs"]
+mod datagen;
+
+const N: usize = 1 << 20;
+
+/// Musser's median-of-3 killer sequence for even `n`.
+fn median_of_three_killer(n: usize) -> Vec<i32> {
+    let k = n / 2;
+    let mut v = vec![0i32; n];
+    for i in 1..=k {
+        if i % 2 == 1 {
+            v[i - 1] = i as i32;
+            v[i] = (k + i) as i32;
+        }
+        v[k + i - 1] = (2 * i) as i32;
+    }
+    v
+}
+
+fn main() {
+    let random = datagen::random(N, 1);
+    let organ_pipe: Vec<i32> = (0..N / 2)
+        .chain((0..N / 2).rev())
+        .map(|i| i as i32)
+        .collect();
+    let sawtooth: Vec<i32> = (0..N).map(|i| (i % 1000) as i32).collect();
+    let mut nearly_sorted: Vec<i32> = (0..N as i32).collect();
+    for i in (0..N).step_by(1000) {
+        nearly_sorted.swap(i, N - 1 - i);
+    }
+
+    for (name, data) in [
+        ("random", random),
+        ("median-of-3 killer", median_of_three_killer(N)),
+        ("organ pipe", organ_pipe),
+        ("sawtooth", sawtooth),
+        ("nearly sorted", nearly_sorted),
+    ] {
+        let mut v = data;
+        let stats = wsort::sort_instrumented(&mut v);
+        assert!(wsort::is_sorted(&v));
+        println!(
+            "{:<20} comparisons: {:>12}  swaps: {:>10}  max depth: {}",
+            name, stats.comparisons, stats.swaps, stats.max_depth
+        );
+    }
+}
@@ -0,0 +1,45 @@
+//! Compares the LSD radix sort with WaveSort on random `i32`s of growing
+//! length, to locate the crossover behind `SortConfig::radix_threshold`.
+//!
+//! Run with `cargo bench --bench radix`.
+
+use std::time::Instant;
+
+const SIZES: [usize; 7] = [100, 300, 1_000, 10_000, 100_000, 1_000_000, 100_000_000];
+
+fn main() {
+    for n in SIZES {
+        let mut data = Vec::with_capacity(n);
+        let mut seed: u64 = n as u64;
+        for _ in 0..n {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            data.push((seed >> 32) as i32);
+        }
+        // Repeat short inputs so each measurement covers ~10M elements
+        let reps = (10_000_000 / n).max(1);
+
+        let mut wave = Vec::new();
+        let start = Instant::now();
+        for _ in 0..reps {
+            wave = data.clone();
+            wsort::sort_i32(&mut wave);
+        }
+        let dur_wave = start.elapsed() / reps as u32;
+
+        let mut radix = Vec::new();
+        let start = Instant::now();
+        for _ in 0..reps {
+            radix = data.clone();
+            wsort::sort_radix(&mut radix);
+        }
+        let dur_radix = start.elapsed() / reps as u32;
+
+        assert_eq!(wave, radix);
+        println!(
+            "n = {:>11}  WaveSort: {:.6} s  Radix: {:.6} s",
+            n,
+            dur_wave.as_secs_f64(),
+            dur_radix.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! Times the leaf sort in isolation: many independent runs of 8 to 16
+//! `i32`s, sorted by `sort_i32` (min/max sorting networks, or AVX2 ones with
+//! the `simd` feature) and by `sort_unstable` (the same networks through the
+//! comparator).
+//!
+//! Run with `cargo bench --bench small_sort`, with and without
+//! `--features simd`.
+
+#[allow(dead_code)]
+#[path = "../src/datagen.rs"]
+mod datagen;
+
+use std::time::Instant;
+
+const N: usize = 16_000_000;
+
+fn main() {
+    let data = datagen::random(N, 1);
+
+    for run in 8..=16 {
+        let mut simd = data.clone();
+        let start = Instant::now();
+        for chunk in simd.chunks_mut(run) {
+            wsort::sort_i32(chunk);
+        }
+        let dur_simd = start.elapsed();
+
+        let mut scalar = data.clone();
+        let start = Instant::now();
+        for chunk in scalar.chunks_mut(run) {
+            wsort::sort_unstable(chunk);
+        }
+        let dur_scalar = start.elapsed();
+
+        assert_eq!(simd, scalar);
+        println!(
+            "runs of {:>2}  sort_i32: {:.6} s  sort_unstable: {:.6} s",
+            run,
+            dur_simd.as_secs_f64(),
+            dur_scalar.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,38 @@
+//! Compares insertion-sort cutoffs for the pure Rust WaveSort.
+//!
+//! Run with `cargo bench --bench thresholds`.
+
+#[allow(dead_code)]
+#[path = "../src/datagen.rs"]
+mod datagen;
+
+use std::time::Instant;
+use wsort::SortConfig;
+
+const N: usize = 10_000_000;
+
+fn main() {
+    let data = datagen::random(N, 1);
+
+    for threshold in [16, wsort::DEFAULT_INSERTION_THRESHOLD, 64] {
+        let cfg = SortConfig {
+            insertion_threshold: threshold,
+            ..Default::default()
+        };
+        let mut v = data.clone();
+        let start = Instant::now();
+        wsort::sort_with_config(&mut v, &cfg);
+        let dur = start.elapsed();
+        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+        let default = if threshold == wsort::DEFAULT_INSERTION_THRESHOLD {
+            " (default)"
+        } else {
+            ""
+        };
+        println!(
+            "threshold {:>2}: {:.6} s{default}",
+            threshold,
+            dur.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,40 @@
+//! Times tiny slices of 2 to 8 `i32`s: `sort_unstable` finishes them with a
+//! compare-exchange network, `sort` (stable) with insertion sort.
+//!
+//! Run with `cargo bench --bench tiny`.
+
+#[allow(dead_code)]
+#[path = "../src/datagen.rs"]
+mod datagen;
+
+use std::hint::black_box;
+use std::time::Instant;
+
+const N: usize = 8_400_000;
+
+fn main() {
+    let data = datagen::random(N, 1);
+
+    for len in 2..=8 {
+        let mut network = data.clone();
+        let start = Instant::now();
+        for chunk in network.chunks_exact_mut(len) {
+            wsort::sort_unstable(black_box(chunk));
+        }
+        let dur_network = start.elapsed();
+
+        let mut insertion = data.clone();
+        let start = Instant::now();
+        for chunk in insertion.chunks_exact_mut(len) {
+            wsort::sort(black_box(chunk));
+        }
+        let dur_insertion = start.elapsed();
+
+        assert_eq!(network, insertion);
+        println!(
+            "len {len}  network: {:.6} s  insertion: {:.6} s",
+            dur_network.as_secs_f64(),
+            dur_insertion.as_secs_f64()
+        );
+    }
+}
@@ -1,46 +1,171 @@
+#[cfg(feature = "asm")]
 use std::env;
+#[cfg(feature = "asm")]
 use std::process::Command;
 
 fn main() {
-    let out_dir = env::var("OUT_DIR").unwrap();
-    let profile = env::var("PROFILE").unwrap_or_else(|_| "release".to_string());
     let asm_src = "src/wavesort.asm";
-    let obj_file = format!("{}/wavesort.o", out_dir);
-    let lib_file = "libwavesort.a";
+    let aarch64_src = "src/wavesort_aarch64.S";
 
-    // 1. Assemble the ASM file using NASM
-    // Detect OS to set the correct format
-    let format = if cfg!(target_os = "macos") {
-        "macho64"
-    } else {
-        "elf64"
-    };
+    // Re-run build script if the ASM files change
+    println!("cargo:rerun-if-changed={}", asm_src);
+    println!("cargo:rerun-if-changed={}", aarch64_src);
+    println!("cargo:rerun-if-env-changed=PROFILE");
+    println!("cargo:rerun-if-env-changed=CC");
+    println!("cargo:rerun-if-env-changed=WSORT_NASM");
+    println!("cargo:rerun-if-env-changed=WSORT_AR");
+    println!("cargo:rerun-if-env-changed=WSORT_ARCH");
+    println!("cargo::rustc-check-cfg=cfg(wsort_asm_real)");
+    println!("cargo::rustc-check-cfg=cfg(wsort_arch, values(\"generic\", \"modern\"))");
 
-    let nasm_opt = if profile == "debug" { "-O0" } else { "-O3" };
-    let status = Command::new("nasm")
-        .args(&["-f", format, nasm_opt, asm_src, "-o", &obj_file])
-        .status()
-        .expect("Failed to run nasm. Is it installed?");
+    #[cfg(feature = "asm")]
+    build_asm(asm_src, aarch64_src);
+}
 
-    if !status.success() {
-        panic!("NASM compilation failed");
+/// Assembles and links the backend for the target, or warns and returns if
+/// it cannot be built here.
+#[cfg(feature = "asm")]
+fn build_asm(asm_src: &str, aarch64_src: &str) {
+    // 0. Decide whether the assembly backend can be built at all
+    let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    if target_arch == "wasm32" {
+        // No assembler or native archive for WASM; the Rust sort is the backend
+        return;
+    }
+    if target_arch != "x86_64" && target_arch != "aarch64" {
+        println!(
+            "cargo:warning=no assembly backend for {}; \
+             wavesort_asm_safe falls back to the pure Rust sort",
+            target_arch
+        );
+        return;
+    }
+    let nasm = tool("WSORT_NASM", "nasm");
+    if target_arch == "x86_64" && !nasm_available(&nasm) {
+        println!(
+            "cargo:warning={} not found; building without the assembly backend \
+             (wavesort_asm_safe falls back to the pure Rust sort; set WSORT_NASM \
+             to point at NASM)",
+            nasm
+        );
+        return;
     }
 
-    // 2. Create a static library (archive) from the object file
-    let status = Command::new("ar")
-        .args(&["crus", &format!("{}/{}", out_dir, lib_file), &obj_file])
-        .status()
-        .expect("Failed to run ar");
+    // 1. Assemble the source for the target architecture and archive it as
+    // `wavesort`. `cc` picks the archiver (lib.exe on MSVC, the target's `ar`
+    // elsewhere) and emits the link lines; the GNU Windows target probes
+    // for one instead.
+    let mut lib = cc::Build::new();
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let target_env = env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default();
+    match env::var("WSORT_AR") {
+        Ok(ar) if !ar.is_empty() => {
+            lib.archiver(ar);
+        }
+        _ if target_os == "windows" && target_env == "gnu" => {
+            lib.archiver(mingw_archiver());
+        }
+        _ => {}
+    }
+    if target_arch == "aarch64" {
+        // GAS syntax with C preprocessor conditionals, so go through the C compiler
+        lib.file(aarch64_src);
+    } else {
+        // nasm-rs picks the output format (elf64, macho64, win64) for the target
+        let profile = env::var("PROFILE").unwrap_or_else(|_| "release".to_string());
+        let variant = asm_variant();
+        println!("cargo:rustc-cfg=wsort_arch=\"{}\"", variant);
+        let mut nasm_build = nasm_rs::Build::new();
+        nasm_build
+            .nasm(&nasm)
+            .file(asm_src)
+            .flag(if profile == "debug" { "-O0" } else { "-O3" });
+        if variant == "modern" {
+            nasm_build.define("WSORT_MODERN", None);
+        }
+        let objects = nasm_build
+            .compile_objects()
+            .unwrap_or_else(|e| panic!("Assembling the WaveSort backend failed: {}", e));
+        lib.objects(objects);
+    }
+    lib.compile("wavesort");
 
-    if !status.success() {
-        panic!("Failed to create static library");
+    // 2. Only now is the assembly known to be real, so every early return
+    // above leaves `wsort_asm_real` unset.
+    println!("cargo:rustc-cfg=wsort_asm_real");
+}
+
+/// Returns the tool named by the environment variable `var`, or `default`.
+/// Lets builds with pinned tool paths (e.g. Nix) skip `PATH` lookup.
+#[cfg(feature = "asm")]
+fn tool(var: &str, default: &str) -> String {
+    env::var(var)
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// Probes for NASM by asking it for its version.
+#[cfg(feature = "asm")]
+fn nasm_available(nasm: &str) -> bool {
+    Command::new(nasm)
+        .arg("-v")
+        .output()
+        .is_ok_and(|out| out.status.success())
+}
+
+/// x86-64 kernel variants `WSORT_ARCH` may name. `modern` unrolls the AVX2
+/// block swap further for wide out-of-order cores (Zen 3, Skylake and later).
+#[cfg(feature = "asm")]
+const ASM_VARIANTS: [&str; 2] = ["generic", "modern"];
+
+/// Picks the x86-64 kernel variant: `WSORT_ARCH` if set, else `modern` when
+/// the target CPU enables AVX2 (e.g. `-C target-cpu=znver3`), else `generic`.
+#[cfg(feature = "asm")]
+fn asm_variant() -> &'static str {
+    match env::var("WSORT_ARCH") {
+        Ok(arch) if !arch.is_empty() => ASM_VARIANTS
+            .into_iter()
+            .find(|v| *v == arch)
+            .unwrap_or_else(|| {
+                panic!(
+                    "unknown WSORT_ARCH {:?} (expected one of {})",
+                    arch,
+                    ASM_VARIANTS.join(", ")
+                )
+            }),
+        _ => {
+            let features = env::var("CARGO_CFG_TARGET_FEATURE").unwrap_or_default();
+            if features.split(',').any(|f| f == "avx2") {
+                "modern"
+            } else {
+                "generic"
+            }
+        }
     }
+}
 
-    // 3. Tell Cargo to link the library
-    println!("cargo:rustc-link-search=native={}", out_dir);
-    println!("cargo:rustc-link-lib=static=wavesort");
+/// Archivers tried in order for the GNU Windows target. Cross toolchains
+/// often install only the prefixed name, and MSYS2 shells only `ar`.
+#[cfg(feature = "asm")]
+const MINGW_ARCHIVERS: [&str; 3] = ["x86_64-w64-mingw32-ar", "ar", "llvm-ar"];
 
-    // Re-run build script if the ASM file changes
-    println!("cargo:rerun-if-changed={}", asm_src);
-    println!("cargo:rerun-if-env-changed=PROFILE");
+/// Returns the first of [`MINGW_ARCHIVERS`] that runs.
+#[cfg(feature = "asm")]
+fn mingw_archiver() -> &'static str {
+    MINGW_ARCHIVERS
+        .into_iter()
+        .find(|ar| {
+            Command::new(ar)
+                .arg("--version")
+                .output()
+                .is_ok_and(|out| out.status.success())
+        })
+        .unwrap_or_else(|| {
+            panic!(
+                "no archiver found for the GNU Windows target (tried {}); \
+                 set WSORT_AR to one",
+                MINGW_ARCHIVERS.join(", ")
+            )
+        })
 }
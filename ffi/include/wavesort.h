@@ -0,0 +1,61 @@
+#ifndef WAVESORT_H
+#define WAVESORT_H
+
+/* Generated by cbindgen from ffi/cbindgen.toml. Do not edit by hand. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// Sorts `len` int32_t values in place with the assembly WaveSort.
+void wave_sort(int32_t *arr, size_t len);
+
+// Sorts `len` int64_t values in place with the assembly WaveSort.
+void wave_sort_i64(int64_t *arr, size_t len);
+
+// Sorts `len` uint32_t values in place with the assembly WaveSort.
+void wave_sort_u32(uint32_t *arr, size_t len);
+
+// Sorts `len` int32_t values in place, descending when `descending` is
+// nonzero and ascending otherwise. On x86-64 both directions use SSE2; call
+// `wave_sort_avx2` for an ascending sort on CPUs with AVX2.
+void wave_sort_cmp(int32_t *arr, size_t len, int descending);
+
+// Sorts `len` int16_t values in place with the assembly WaveSort.
+void wave_sort_i16(int16_t *arr, size_t len);
+
+#if defined(__x86_64__) || defined(_M_X64)
+// AVX2 variant of `wave_sort`. Only call it on CPUs that support AVX2.
+void wave_sort_avx2(int32_t *arr, size_t len);
+
+// SSE2 variant of `wave_sort`, for x86-64 CPUs without AVX2.
+void wave_sort_sse2(int32_t *arr, size_t len);
+#endif
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif
+
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Sorts `len` `i32`s starting at `arr` with the pure Rust WaveSort.
+//
+// C entry point mirroring the assembly `wave_sort`, declared in
+// `ffi/include/wavesort.h`. A null `arr` or `len < 2` is a no-op.
+//
+// # Safety
+//
+// Unless null, `arr` must be valid for reads and writes of `len` `i32`s.
+void wave_sort_rust(int32_t *arr, size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* WAVESORT_H */
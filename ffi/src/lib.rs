@@ -0,0 +1,10 @@
+//! C static library for WaveSort.
+//!
+//! Builds `libwsort_ffi.a`, which exports the symbols declared in
+//! `include/wavesort.h`: the assembly entry points (`wave_sort`,
+//! `wave_sort_avx2`, `wave_sort_sse2`, `wave_sort_i64`, `wave_sort_u32`,
+//! `wave_sort_cmp`, `wave_sort_i16`) and the pure Rust `wave_sort_rust`.
+//! Regenerate the header with
+//! `cbindgen --config ffi/cbindgen.toml --crate wsort-ffi --output ffi/include/wavesort.h`.
+
+pub use wsort::wave_sort_rust;
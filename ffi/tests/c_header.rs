@@ -0,0 +1,57 @@
+//! Compiles `wavesort_test.c` against `include/wavesort.h` and the static
+//! library, then runs it.
+
+#![cfg(unix)]
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn c_program_links_and_sorts() {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let lib = staticlib();
+
+    let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("wavesort_test");
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(&cc)
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest.join("include"))
+        .arg(manifest.join("tests/wavesort_test.c"))
+        .arg(&lib)
+        .args(["-lpthread", "-ldl", "-lm"])
+        .arg("-o")
+        .arg(&out)
+        .status()
+        .unwrap_or_else(|e| panic!("failed to run {cc}: {e}"));
+    assert!(status.success(), "compiling the C test failed");
+
+    let output = Command::new(&out).output().unwrap();
+    assert!(
+        output.status.success(),
+        "C test failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Finds the `libwsort_ffi-<hash>.a` built alongside this test binary in
+/// `<target>/<profile>/deps`. `cargo test` does not copy it up a level.
+fn staticlib() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    let deps = exe.parent().unwrap();
+    fs::read_dir(deps)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("libwsort_ffi-") && name.ends_with(".a")
+        })
+        .max_by_key(|e| e.metadata().and_then(|m| m.modified()).ok())
+        .map(|e| e.path())
+        .expect("libwsort_ffi staticlib not found")
+}
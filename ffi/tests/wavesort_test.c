@@ -0,0 +1,113 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "wavesort.h"
+
+#define N 10000
+
+static uint64_t state = 42;
+
+static uint64_t next(void) {
+    state = state * 6364136223846793005ULL + 1442695040888963407ULL;
+    return state >> 33;
+}
+
+static int check_i32(const char *name, void (*sort)(int32_t *, size_t)) {
+    static int32_t arr[N];
+    for (size_t i = 0; i < N; i++) {
+        arr[i] = (int32_t)next() - (1 << 30);
+    }
+    sort(arr, N);
+    for (size_t i = 1; i < N; i++) {
+        if (arr[i - 1] > arr[i]) {
+            fprintf(stderr, "%s: unsorted at %zu\n", name, i);
+            return 1;
+        }
+    }
+    sort(NULL, 0);
+    sort(arr, 0);
+    return 0;
+}
+
+static int check_i64(void) {
+    static int64_t arr[N];
+    for (size_t i = 0; i < N; i++) {
+        arr[i] = (int64_t)(next() << 31) - (int64_t)next();
+    }
+    wave_sort_i64(arr, N);
+    for (size_t i = 1; i < N; i++) {
+        if (arr[i - 1] > arr[i]) {
+            fprintf(stderr, "wave_sort_i64: unsorted at %zu\n", i);
+            return 1;
+        }
+    }
+    return 0;
+}
+
+static int check_u32(void) {
+    static uint32_t arr[N];
+    for (size_t i = 0; i < N; i++) {
+        arr[i] = (uint32_t)next() + 0x7fff0000u;
+    }
+    wave_sort_u32(arr, N);
+    for (size_t i = 1; i < N; i++) {
+        if (arr[i - 1] > arr[i]) {
+            fprintf(stderr, "wave_sort_u32: unsorted at %zu\n", i);
+            return 1;
+        }
+    }
+    return 0;
+}
+
+static int check_cmp(int descending) {
+    static int32_t arr[N];
+    for (size_t i = 0; i < N; i++) {
+        arr[i] = (int32_t)next();
+    }
+    arr[0] = INT32_MIN;
+    arr[1] = INT32_MAX;
+    wave_sort_cmp(arr, N, descending);
+    for (size_t i = 1; i < N; i++) {
+        if (descending ? arr[i - 1] < arr[i] : arr[i - 1] > arr[i]) {
+            fprintf(stderr, "wave_sort_cmp(%d): out of order at %zu\n", descending, i);
+            return 1;
+        }
+    }
+    return 0;
+}
+
+static int check_i16(void) {
+    static int16_t arr[N];
+    for (size_t i = 0; i < N; i++) {
+        arr[i] = (int16_t)next();
+    }
+    arr[0] = INT16_MIN;
+    arr[1] = INT16_MAX;
+    wave_sort_i16(arr, N);
+    for (size_t i = 1; i < N; i++) {
+        if (arr[i - 1] > arr[i]) {
+            fprintf(stderr, "wave_sort_i16: unsorted at %zu\n", i);
+            return 1;
+        }
+    }
+    return 0;
+}
+
+int main(void) {
+    int failed = 0;
+    failed |= check_i32("wave_sort", wave_sort);
+    failed |= check_i32("wave_sort_rust", wave_sort_rust);
+#if defined(__x86_64__) || defined(_M_X64)
+    failed |= check_i32("wave_sort_sse2", wave_sort_sse2);
+#endif
+    failed |= check_i64();
+    failed |= check_u32();
+    failed |= check_cmp(0);
+    failed |= check_cmp(1);
+    failed |= check_i16();
+    if (failed) {
+        return EXIT_FAILURE;
+    }
+    puts("ok");
+    return EXIT_SUCCESS;
+}
@@ -0,0 +1,37 @@
+//! Differential fuzz target: checks the pure Rust WaveSort
+//! (`sort_unstable`) and the assembly sort against `slice::sort` on
+//! arbitrary input.
+//!
+//! ```sh
+//! cargo install cargo-fuzz
+//! cd fuzz
+//! cargo +nightly fuzz run diff_sort corpus/diff_sort
+//! ```
+//!
+//! The seed corpus in `corpus/diff_sort` covers the empty input, a single
+//! element, all-equal, sorted and reversed runs. Without NASM installed the
+//! assembly sort falls back to the Rust one, so install it to fuzz both.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Input is read as little-endian i32s; trailing bytes are ignored. This keeps
+// the seed corpus easy to write by hand.
+fuzz_target!(|data: &[u8]| {
+    let input: Vec<i32> = data
+        .chunks_exact(4)
+        .map(|c| i32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect();
+
+    let mut expected = input.clone();
+    expected.sort();
+
+    let mut rust = input.clone();
+    wsort::sort_unstable(&mut rust);
+    assert_eq!(rust, expected, "wsort::sort_unstable");
+
+    let mut asm = input;
+    wsort::sort_asm(&mut asm);
+    assert_eq!(asm, expected, "wsort::sort_asm");
+});
@@ -0,0 +1,430 @@
+#[cfg(wsort_asm_real)]
+use std::ffi::c_int;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::OnceLock;
+
+use crate::SortError;
+
+// -----------------------------------------------------------------------------
+// FFI Declaration (The Wiring)
+// -----------------------------------------------------------------------------
+type SortFn = unsafe extern "C" fn(arr: *mut i32, len: usize);
+
+#[cfg(wsort_asm_real)]
+unsafe extern "C" {
+    /// Scalar 32-bit entry point defined in wavesort.asm / wavesort_aarch64.S
+    /// Signature: void wave_sort(int32_t *arr, size_t len);
+    fn wave_sort(arr: *mut i32, len: usize);
+
+    /// Scalar 64-bit variant defined in wavesort.asm / wavesort_aarch64.S
+    /// Signature: void wave_sort_i64(int64_t *arr, size_t len);
+    fn wave_sort_i64(arr: *mut i64, len: usize);
+
+    /// Scalar unsigned 32-bit variant defined in wavesort.asm / wavesort_aarch64.S
+    /// Signature: void wave_sort_u32(uint32_t *arr, size_t len);
+    fn wave_sort_u32(arr: *mut u32, len: usize);
+
+    /// 32-bit entry point in either direction, defined in wavesort.asm /
+    /// wavesort_aarch64.S. Nonzero `descending` sorts largest first; on
+    /// x86-64 both directions run the SSE2 tier.
+    /// Signature: void wave_sort_cmp(int32_t *arr, size_t len, int descending);
+    fn wave_sort_cmp(arr: *mut i32, len: usize, descending: c_int);
+
+    /// Signed 16-bit variant defined in wavesort.asm / wavesort_aarch64.S
+    /// Signature: void wave_sort_i16(int16_t *arr, size_t len);
+    fn wave_sort_i16(arr: *mut i16, len: usize);
+}
+
+#[cfg(all(wsort_asm_real, target_arch = "x86_64"))]
+unsafe extern "C" {
+    /// AVX2 32-bit entry point defined in wavesort.asm
+    /// Signature: void wave_sort_avx2(int32_t *arr, size_t len);
+    fn wave_sort_avx2(arr: *mut i32, len: usize);
+
+    /// SSE2 32-bit entry point defined in wavesort.asm
+    /// Signature: void wave_sort_sse2(int32_t *arr, size_t len);
+    fn wave_sort_sse2(arr: *mut i32, len: usize);
+}
+
+// Only x86-64 has the AVX2 and SSE2 entry points; `AsmTier::detect` never
+// selects them elsewhere.
+#[cfg(all(wsort_asm_real, not(target_arch = "x86_64")))]
+use self::{wave_sort as wave_sort_avx2, wave_sort as wave_sort_sse2};
+
+// Stubs with the same signatures, used when the assembly was not built. They
+// keep the C symbols too, so `ffi/include/wavesort.h` links either way.
+#[cfg(not(wsort_asm_real))]
+use fallback::{
+    wave_sort, wave_sort_avx2, wave_sort_cmp, wave_sort_i16, wave_sort_i64, wave_sort_sse2,
+    wave_sort_u32,
+};
+
+#[cfg(not(wsort_asm_real))]
+mod fallback {
+    use std::ffi::c_int;
+    use std::slice;
+
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn wave_sort(arr: *mut i32, len: usize) {
+        unsafe { crate::wave_sort_rust(arr, len) }
+    }
+
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn wave_sort_avx2(arr: *mut i32, len: usize) {
+        unsafe { wave_sort(arr, len) }
+    }
+
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn wave_sort_sse2(arr: *mut i32, len: usize) {
+        unsafe { wave_sort(arr, len) }
+    }
+
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn wave_sort_i64(arr: *mut i64, len: usize) {
+        if arr.is_null() || len < 2 {
+            return;
+        }
+        unsafe { crate::wavesort_rust::sort(slice::from_raw_parts_mut(arr, len)) }
+    }
+
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn wave_sort_u32(arr: *mut u32, len: usize) {
+        if arr.is_null() || len < 2 {
+            return;
+        }
+        unsafe { crate::wavesort_rust::sort(slice::from_raw_parts_mut(arr, len)) }
+    }
+
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn wave_sort_cmp(arr: *mut i32, len: usize, descending: c_int) {
+        if arr.is_null() || len < 2 {
+            return;
+        }
+        let arr = unsafe { slice::from_raw_parts_mut(arr, len) };
+        if descending != 0 {
+            crate::wavesort_rust::sort_desc(arr);
+        } else {
+            crate::wavesort_rust::sort(arr);
+        }
+    }
+
+    #[unsafe(no_mangle)]
+    pub unsafe extern "C" fn wave_sort_i16(arr: *mut i16, len: usize) {
+        if arr.is_null() || len < 2 {
+            return;
+        }
+        unsafe { crate::wavesort_rust::sort(slice::from_raw_parts_mut(arr, len)) }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Runtime Dispatch
+// -----------------------------------------------------------------------------
+
+/// Instruction-set tier of the 32-bit assembly backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsmTier {
+    /// Plain integer instructions, available on every CPU.
+    Scalar,
+    /// Partition scans and block swaps vectorized with SSE2, which every
+    /// x86-64 CPU has.
+    Sse2,
+    /// Vectorized partitioning and block swaps using AVX2.
+    Avx2,
+}
+
+impl AsmTier {
+    /// Returns the best tier supported by the running CPU: AVX2, then SSE2
+    /// on x86-64, then scalar.
+    pub fn detect() -> AsmTier {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return AsmTier::Avx2;
+            }
+            AsmTier::Sse2
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        AsmTier::Scalar
+    }
+
+    /// Returns `true` if the running CPU can execute this tier.
+    pub fn is_supported(self) -> bool {
+        match self {
+            AsmTier::Scalar => true,
+            AsmTier::Sse2 => cfg!(target_arch = "x86_64"),
+            AsmTier::Avx2 => AsmTier::detect() == AsmTier::Avx2,
+        }
+    }
+
+    fn entry(self) -> SortFn {
+        match self {
+            AsmTier::Scalar => wave_sort,
+            AsmTier::Sse2 => wave_sort_sse2,
+            AsmTier::Avx2 => wave_sort_avx2,
+        }
+    }
+}
+
+/// x86-64 kernel variant linked into the AVX2 entry point, chosen at build
+/// time by `WSORT_ARCH` or the target CPU: `"generic"` or `"modern"`.
+/// `None` when no x86-64 assembly was built.
+#[cfg(wsort_arch = "modern")]
+pub const ASM_VARIANT: Option<&str> = Some("modern");
+#[cfg(wsort_arch = "generic")]
+pub const ASM_VARIANT: Option<&str> = Some("generic");
+#[cfg(not(any(wsort_arch = "generic", wsort_arch = "modern")))]
+pub const ASM_VARIANT: Option<&str> = None;
+
+/// Whether the assembly backend was assembled and linked.
+///
+/// `false` when the `asm` feature fell back to the pure Rust stubs, because
+/// NASM was missing or the target has no assembly. The crate itself keys
+/// the same choice on `cfg(wsort_asm_real)`, which `build.rs` sets only
+/// after the library is built; cfgs do not reach dependents, so this
+/// constant is how downstream code tells the two apart.
+pub const HAS_ASM: bool = cfg!(wsort_asm_real);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn selected_entry() -> SortFn {
+    static ENTRY: OnceLock<SortFn> = OnceLock::new();
+    *ENTRY.get_or_init(|| AsmTier::detect().entry())
+}
+
+// -----------------------------------------------------------------------------
+// Safe Wrappers
+// -----------------------------------------------------------------------------
+
+/// Safe Rust wrapper for the ASM function.
+///
+/// Uses the best entry point the CPU supports, AVX2, then SSE2, then scalar
+/// (see [`AsmTier::detect`]). The choice is made once and cached.
+///
+/// The assembly requires `arr` to be aligned for `i32`. Every soundly built
+/// slice is; one transmuted from an unaligned byte buffer is already
+/// undefined behavior, and debug builds assert against it here rather than
+/// fault inside the vector loads.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn wavesort_asm_safe(arr: &mut [i32]) {
+    debug_assert_aligned(arr);
+    if arr.len() < 2 {
+        return;
+    }
+    let entry = selected_entry();
+    unsafe {
+        entry(arr.as_mut_ptr(), arr.len());
+    }
+}
+
+/// Safe Rust wrapper for the ASM function.
+///
+/// There is no assembly backend for WASM, so this is the pure Rust sort.
+#[cfg(target_arch = "wasm32")]
+pub fn wavesort_asm_safe(arr: &mut [i32]) {
+    crate::wavesort_rust::sort(arr);
+}
+
+/// Checked counterpart of [`wavesort_asm_safe`].
+///
+/// A slice always passes the checks in [`try_wavesort_asm_raw`], so this
+/// only fails if `arr` was built unsoundly, for example from a byte buffer
+/// that is not 4-byte aligned.
+pub fn try_wavesort_asm(arr: &mut [i32]) -> Result<(), SortError> {
+    unsafe { try_wavesort_asm_raw(arr.as_mut_ptr(), arr.len()) }
+}
+
+/// Validates `arr` and `len`, then sorts like [`wavesort_asm_safe`].
+///
+/// Returns an error instead of calling into the assembly if `arr` is null,
+/// not aligned for `i32`, or `len` is longer than any `i32` slice can be.
+/// Nothing is read or written when an error is returned.
+///
+/// # Safety
+///
+/// If the checks pass, `arr` must be valid for reads and writes of `len`
+/// `i32`s.
+pub unsafe fn try_wavesort_asm_raw(arr: *mut i32, len: usize) -> Result<(), SortError> {
+    if arr.is_null() {
+        return Err(SortError::NullPointer);
+    }
+    let align = align_of::<i32>();
+    if !(arr as usize).is_multiple_of(align) {
+        return Err(SortError::Misaligned {
+            addr: arr as usize,
+            align,
+        });
+    }
+    let max = isize::MAX as usize / size_of::<i32>();
+    if len > max {
+        return Err(SortError::TooLong { len, max });
+    }
+    wavesort_asm_safe(unsafe { std::slice::from_raw_parts_mut(arr, len) });
+    Ok(())
+}
+
+/// Sorts with a specific assembly tier instead of the detected one.
+///
+/// # Panics
+///
+/// Panics if the running CPU does not support `tier`.
+pub fn wavesort_asm_tier(arr: &mut [i32], tier: AsmTier) {
+    assert!(tier.is_supported(), "{tier:?} is not supported by this CPU");
+    debug_assert_aligned(arr);
+    if arr.len() < 2 {
+        return;
+    }
+    unsafe {
+        tier.entry()(arr.as_mut_ptr(), arr.len());
+    }
+}
+
+/// Asserts in debug builds that `arr` is aligned for `T`, as the assembly
+/// routines assume.
+#[inline(always)]
+fn debug_assert_aligned<T>(arr: &[T]) {
+    debug_assert!(
+        (arr.as_ptr() as usize).is_multiple_of(align_of::<T>()),
+        "slice at {:p} is not aligned to {} bytes",
+        arr.as_ptr(),
+        align_of::<T>()
+    );
+}
+
+/// Safe Rust wrapper for the 64-bit ASM function.
+///
+/// Debug builds check alignment like [`wavesort_asm_safe`].
+pub fn wavesort_asm_safe_i64(arr: &mut [i64]) {
+    debug_assert_aligned(arr);
+    if arr.len() < 2 {
+        return;
+    }
+    unsafe {
+        wave_sort_i64(arr.as_mut_ptr(), arr.len());
+    }
+}
+
+/// Safe Rust wrapper for the unsigned 32-bit ASM function.
+///
+/// Debug builds check alignment like [`wavesort_asm_safe`].
+pub fn wavesort_asm_safe_u32(arr: &mut [u32]) {
+    debug_assert_aligned(arr);
+    if arr.len() < 2 {
+        return;
+    }
+    unsafe {
+        wave_sort_u32(arr.as_mut_ptr(), arr.len());
+    }
+}
+
+/// Safe Rust wrapper for the signed 16-bit ASM function.
+///
+/// Debug builds check alignment like [`wavesort_asm_safe`].
+pub fn wavesort_asm_safe_i16(arr: &mut [i16]) {
+    debug_assert_aligned(arr);
+    if arr.len() < 2 {
+        return;
+    }
+    unsafe {
+        wave_sort_i16(arr.as_mut_ptr(), arr.len());
+    }
+}
+
+/// Safe Rust wrapper for the 32-bit ASM function that sorts in either
+/// direction, largest first when `desc` is set.
+///
+/// Ascending sorts are [`wavesort_asm_safe`], on the detected
+/// [`AsmTier`]. Descending sorts run the assembly body with the partition
+/// compares swapped, vectorized with SSE2 on x86-64. Debug builds check
+/// alignment like [`wavesort_asm_safe`].
+pub fn wavesort_asm_safe_dir(arr: &mut [i32], desc: bool) {
+    if !desc {
+        wavesort_asm_safe(arr);
+        return;
+    }
+    debug_assert_aligned(arr);
+    if arr.len() < 2 {
+        return;
+    }
+    unsafe {
+        wave_sort_cmp(arr.as_mut_ptr(), arr.len(), 1);
+    }
+}
+
+/// Safe Rust wrapper for the descending 32-bit ASM function.
+///
+/// Same as [`wavesort_asm_safe_dir`] with `desc` set.
+pub fn wavesort_asm_safe_desc(arr: &mut [i32]) {
+    wavesort_asm_safe_dir(arr, true);
+}
+
+// -----------------------------------------------------------------------------
+// Generic Dispatch
+// -----------------------------------------------------------------------------
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Element types with an assembly sort entry point.
+///
+/// Implemented for `i16`, `i32`, `i64` and `u32`, and sealed so that only types
+/// the assembly actually handles can implement it. Used by [`asm_sort`].
+pub trait AsmSortable: sealed::Sealed + Sized {
+    /// Sorts `len` elements starting at `ptr` with this type's assembly
+    /// routine.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reads and writes of `len` elements and
+    /// aligned for `Self`.
+    unsafe fn sort_raw(ptr: *mut Self, len: usize);
+}
+
+impl sealed::Sealed for i16 {}
+impl sealed::Sealed for i32 {}
+impl sealed::Sealed for i64 {}
+impl sealed::Sealed for u32 {}
+
+impl AsmSortable for i16 {
+    unsafe fn sort_raw(ptr: *mut i16, len: usize) {
+        unsafe { wave_sort_i16(ptr, len) }
+    }
+}
+
+impl AsmSortable for i32 {
+    /// Uses the same detected tier as [`wavesort_asm_safe`].
+    unsafe fn sort_raw(ptr: *mut i32, len: usize) {
+        wavesort_asm_safe(unsafe { std::slice::from_raw_parts_mut(ptr, len) });
+    }
+}
+
+impl AsmSortable for i64 {
+    unsafe fn sort_raw(ptr: *mut i64, len: usize) {
+        unsafe { wave_sort_i64(ptr, len) }
+    }
+}
+
+impl AsmSortable for u32 {
+    unsafe fn sort_raw(ptr: *mut u32, len: usize) {
+        unsafe { wave_sort_u32(ptr, len) }
+    }
+}
+
+/// Sorts the slice in place with the assembly routine for its element type.
+///
+/// ```
+/// let mut small = [3i32, -1, 2];
+/// wsort::asm_sort(&mut small);
+/// assert_eq!(small, [-1, 2, 3]);
+///
+/// let mut wide = [u32::MAX, 0, 7];
+/// wsort::asm_sort(&mut wide);
+/// assert_eq!(wide, [0, 7, u32::MAX]);
+/// ```
+pub fn asm_sort<T: AsmSortable>(arr: &mut [T]) {
+    debug_assert_aligned(arr);
+    if arr.len() < 2 {
+        return;
+    }
+    unsafe { T::sort_raw(arr.as_mut_ptr(), arr.len()) }
+}
@@ -0,0 +1,104 @@
+//! Reads whitespace-separated integers from stdin and prints them sorted,
+//! one per line.
+//!
+//! Usage: wsort-cli [--desc] [--asm] [--check]
+
+use std::io::{self, BufWriter, Read, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: wsort-cli [--desc] [--asm] [--check]
+
+Reads whitespace-separated integers from stdin and prints them sorted.
+
+  --desc    sort in descending order
+  --asm     use the assembly backend
+  --check   verify the output is sorted before printing it";
+
+#[derive(Default)]
+struct Options {
+    desc: bool,
+    asm: bool,
+    check: bool,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut opts = Options::default();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--desc" => opts.desc = true,
+            "--asm" => opts.asm = true,
+            "--check" => opts.check = true,
+            "-h" | "--help" => return Err(String::new()),
+            other => return Err(format!("unknown argument `{other}`")),
+        }
+    }
+    Ok(opts)
+}
+
+fn read_input() -> Result<Vec<i32>, String> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|e| format!("failed to read stdin: {e}"))?;
+    input
+        .split_whitespace()
+        .map(|tok| {
+            tok.parse::<i32>()
+                .map_err(|e| format!("invalid integer `{tok}`: {e}"))
+        })
+        .collect()
+}
+
+fn main() -> ExitCode {
+    let opts = match parse_args() {
+        Ok(opts) => opts,
+        Err(msg) => {
+            if !msg.is_empty() {
+                eprintln!("error: {msg}\n");
+            }
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut data = match read_input() {
+        Ok(data) => data,
+        Err(msg) => {
+            eprintln!("error: {msg}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match (opts.asm, opts.desc) {
+        (false, false) => wsort::sort_unstable(&mut data),
+        (false, true) => wsort::sort_desc(&mut data),
+        (true, desc) => {
+            wsort::sort_asm(&mut data);
+            if desc {
+                data.reverse();
+            }
+        }
+    }
+
+    if opts.check {
+        let sorted = if opts.desc {
+            wsort::is_sorted_by(&data, |a, b| b.cmp(a))
+        } else {
+            wsort::is_sorted(&data)
+        };
+        if !sorted {
+            eprintln!("FAILURE: output is not sorted");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    for v in &data {
+        if writeln!(out, "{v}").is_err() {
+            return ExitCode::FAILURE;
+        }
+    }
+    if out.flush().is_err() {
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,96 @@
+use core::cmp::Ordering;
+
+use crate::{SortConfig, parallel, wavesort_rust};
+
+/// Chainable front end over the sorting functions.
+///
+/// Collects the options that would otherwise pick between
+/// [`sort_with_config`](crate::sort_with_config),
+/// [`sort_desc`](crate::sort_desc) and
+/// [`sort_parallel_with_config`](crate::sort_parallel_with_config), and
+/// honors every combination of them, with the insertion threshold applied on
+/// every path. Descending order is a reversed comparator, which the parallel
+/// sorts share between threads:
+///
+/// - With the `rayon` feature, a parallel sort is one WaveSort whose large
+///   downwave splits run their two sides under `rayon::join`. The sides are
+///   disjoint and already in place relative to each other, so nothing is
+///   merged afterwards.
+/// - Without it, or with [`Backend::MergeSort`](crate::Backend::MergeSort),
+///   the slice is cut into halves that are sorted on scoped threads and then
+///   merged in place, so each merge runs in the chosen order too.
+///
+/// ```
+/// let mut v: Vec<i32> = (0..100_000).map(|i| (i * 7919) % 100_003).collect();
+/// wsort::WaveSort::new()
+///     .threshold(48)
+///     .descending(true)
+///     .parallel(true)
+///     .run(&mut v);
+/// assert!(v.windows(2).all(|w| w[0] >= w[1]));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WaveSort {
+    cfg: SortConfig,
+    descending: bool,
+    parallel: bool,
+}
+
+impl WaveSort {
+    /// Ascending, sequential, with the default [`SortConfig`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from custom tuning instead of the default [`SortConfig`].
+    pub fn with_config(cfg: SortConfig) -> Self {
+        WaveSort {
+            cfg,
+            ..Self::default()
+        }
+    }
+
+    /// Sets [`SortConfig::insertion_threshold`].
+    pub fn threshold(mut self, insertion_threshold: usize) -> Self {
+        self.cfg.insertion_threshold = insertion_threshold;
+        self
+    }
+
+    /// Sorts largest first when `descending` is `true`.
+    pub fn descending(mut self, descending: bool) -> Self {
+        self.descending = descending;
+        self
+    }
+
+    /// Splits the sort across threads when `parallel` is `true`, as
+    /// [`sort_parallel_with_config`](crate::sort_parallel_with_config) does.
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// The tuning [`run`](Self::run) uses.
+    pub fn config(&self) -> &SortConfig {
+        &self.cfg
+    }
+
+    /// Sorts `arr` in place with the chosen options.
+    ///
+    /// `T: Send` is required even for sequential sorts, so that whether the
+    /// sort is parallel stays a runtime choice.
+    pub fn run<T: Ord + Send>(&self, arr: &mut [T]) {
+        if self.descending {
+            self.run_by(arr, |a: &T, b: &T| b.cmp(a));
+        } else {
+            self.run_by(arr, T::cmp);
+        }
+    }
+
+    fn run_by<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(&self, arr: &mut [T], compare: F) {
+        if self.parallel {
+            parallel::sort_parallel_by(arr, &self.cfg, &compare);
+        } else {
+            wavesort_rust::sort_by_config(arr, &self.cfg, compare);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+/// Default [`SortConfig::insertion_threshold`]: the longest sub-array that
+/// is finished with insertion sort instead of further wave recursion.
+///
+/// ```
+/// assert_eq!(
+///     wsort::SortConfig::default().insertion_threshold,
+///     wsort::DEFAULT_INSERTION_THRESHOLD,
+/// );
+/// assert_eq!(wsort::DEFAULT_INSERTION_THRESHOLD, 32);
+/// ```
+pub const DEFAULT_INSERTION_THRESHOLD: usize = 32;
+
+/// Algorithm [`sort_with_config`](crate::sort_with_config) runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Unstable, in-place WaveSort.
+    #[default]
+    WaveSort,
+    /// Stable merge sort through a buffer of half the slice, as in
+    /// [`merge_sort`](crate::merge_sort). Without the `std` feature there is
+    /// no buffer and the in-place merge of [`sort`](crate::sort) is used.
+    MergeSort,
+}
+
+/// How WaveSort partitions around a pivot that repeats many times.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Partition {
+    /// Sample the input once at entry and use [`Partition::ThreeWay`] unless
+    /// every sampled key is distinct.
+    #[default]
+    Auto,
+    /// Always split in two around the pivot, skipping the duplicate check.
+    TwoWay,
+    /// Split off the keys equal to the pivot whenever it repeats often in
+    /// the sorted block, so they drop out of further merging.
+    ThreeWay,
+}
+
+/// Tuning knobs for [`sort_with_config`](crate::sort_with_config).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortConfig {
+    /// Sub-arrays of at most this many elements are finished with insertion
+    /// sort instead of further wave recursion; defaults to
+    /// [`DEFAULT_INSERTION_THRESHOLD`]. Values below 2 are treated
+    /// as 2.
+    pub insertion_threshold: usize,
+    /// `i32` slices longer than this are sorted with an LSD radix sort
+    /// instead of WaveSort by [`sort_i32_with_config`](crate::sort_i32_with_config).
+    /// `None` always uses WaveSort. Needs the `std` feature for the radix
+    /// scratch buffer and is ignored without it.
+    pub radix_threshold: Option<usize>,
+    /// [`sort_parallel_with_config`](crate::sort_parallel_with_config) never
+    /// splits work into pieces shorter than this, so slices shorter than
+    /// twice this are sorted on the calling thread.
+    pub parallel_threshold: usize,
+    /// Most threads `sort_parallel_with_config` uses, the calling thread
+    /// included; `1` keeps it sequential. The default `0` means
+    /// [`std::thread::available_parallelism`], looked up when the parallel
+    /// sort starts so that building a config stays free. With the `rayon`
+    /// feature `0` runs on the global rayon pool and other values on a pool
+    /// of that size.
+    pub max_threads: usize,
+    /// Sorting algorithm; defaults to [`Backend::WaveSort`]. `i32` sorts
+    /// ignore it, since equal `i32`s are indistinguishable.
+    pub backend: Backend,
+    /// Partition strategy of [`Backend::WaveSort`]; defaults to
+    /// [`Partition::Auto`].
+    pub partition: Partition,
+}
+
+impl Default for SortConfig {
+    fn default() -> Self {
+        SortConfig {
+            insertion_threshold: DEFAULT_INSERTION_THRESHOLD,
+            radix_threshold: None,
+            parallel_threshold: 1 << 16,
+            max_threads: 0,
+            backend: Backend::WaveSort,
+            partition: Partition::Auto,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::wavesort_rust;
+
+/// Largest `max - min + 1` a counts buffer is allocated for (8 MiB of
+/// counters on 64-bit targets). Wider ranges fall back to WaveSort.
+const MAX_RANGE: u64 = 1 << 20;
+
+/// Inputs shorter than this never take the counting path from
+/// [`detect_and_sort`]: the min/max scan and buffer cost more than they save.
+const MIN_LEN: usize = 256;
+
+/// Number of distinct values in `min..=max`.
+#[inline]
+fn range(min: i32, max: i32) -> u64 {
+    (max as i64 - min as i64 + 1) as u64
+}
+
+/// Counting sort of `arr`, whose values all lie in `min..=max`.
+///
+/// Falls back to WaveSort when the range is wider than [`MAX_RANGE`].
+pub fn sort_i32(arr: &mut [i32], min: i32, max: i32) {
+    sort_i32_with(arr, min, max, &mut Vec::new());
+}
+
+/// [`sort_i32`] with a caller-owned counts buffer that keeps its capacity
+/// across calls.
+pub fn sort_i32_with(arr: &mut [i32], min: i32, max: i32, counts: &mut Vec<usize>) {
+    assert!(
+        min <= max,
+        "sort_counting min {min} is greater than max {max}"
+    );
+    if range(min, max) > MAX_RANGE {
+        wavesort_rust::sort(arr);
+        return;
+    }
+    counts.clear();
+    counts.resize(range(min, max) as usize, 0);
+    for &x in arr.iter() {
+        assert!(
+            (min..=max).contains(&x),
+            "sort_counting value {x} outside {min}..={max}"
+        );
+        counts[(x as i64 - min as i64) as usize] += 1;
+    }
+    let mut out = 0;
+    for (offset, &count) in counts.iter().enumerate() {
+        let value = (min as i64 + offset as i64) as i32;
+        arr[out..out + count].fill(value);
+        out += count;
+    }
+}
+
+/// Counting-sorts `arr` if its values span no more than its length, and
+/// returns whether it did.
+pub fn detect_and_sort(arr: &mut [i32]) -> bool {
+    detect_and_sort_with(arr, &mut Vec::new())
+}
+
+/// [`detect_and_sort`] with a caller-owned counts buffer.
+pub fn detect_and_sort_with(arr: &mut [i32], counts: &mut Vec<usize>) -> bool {
+    if arr.len() < MIN_LEN {
+        return false;
+    }
+    let (min, max) = arr
+        .iter()
+        .fold((i32::MAX, i32::MIN), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+    let span = range(min, max);
+    if span > MAX_RANGE || span > arr.len() as u64 {
+        return false;
+    }
+    sort_i32_with(arr, min, max, counts);
+    true
+}
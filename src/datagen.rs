@@ -0,0 +1,97 @@
+//! Input distributions for the benchmark.
+
+/// Shape of the generated benchmark data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dist {
+    Random,
+    Sorted,
+    Reversed,
+    NearlySorted,
+    AllEqual,
+    Sawtooth,
+}
+
+impl Dist {
+    pub const NAMES: &'static str = "random, sorted, reversed, nearly-sorted, all-equal, sawtooth";
+
+    pub fn parse(name: &str) -> Option<Dist> {
+        Some(match name {
+            "random" => Dist::Random,
+            "sorted" => Dist::Sorted,
+            "reversed" => Dist::Reversed,
+            "nearly-sorted" => Dist::NearlySorted,
+            "all-equal" => Dist::AllEqual,
+            "sawtooth" => Dist::Sawtooth,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dist::Random => "random",
+            Dist::Sorted => "sorted",
+            Dist::Reversed => "reversed",
+            Dist::NearlySorted => "nearly-sorted",
+            Dist::AllEqual => "all-equal",
+            Dist::Sawtooth => "sawtooth",
+        }
+    }
+
+    pub fn generate(self, n: usize, seed: u64) -> Vec<i32> {
+        match self {
+            Dist::Random => random(n, seed),
+            Dist::Sorted => sorted(n),
+            Dist::Reversed => reversed(n),
+            Dist::NearlySorted => nearly_sorted(n, seed),
+            Dist::AllEqual => all_equal(n),
+            Dist::Sawtooth => sawtooth(n),
+        }
+    }
+}
+
+/// Steps the benchmark LCG and returns a value in `0..2^31`.
+fn lcg(seed: &mut u64) -> u64 {
+    *seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+    (*seed / 65536) % 2147483648
+}
+
+/// `n` pseudo-random non-negative integers from the benchmark LCG.
+pub fn random(n: usize, seed: u64) -> Vec<i32> {
+    let mut seed = seed;
+    (0..n).map(|_| lcg(&mut seed) as i32).collect()
+}
+
+/// `0, 1, ..., n - 1`.
+pub fn sorted(n: usize) -> Vec<i32> {
+    (0..n).map(|i| i as i32).collect()
+}
+
+/// `n - 1, ..., 1, 0`.
+pub fn reversed(n: usize) -> Vec<i32> {
+    (0..n).rev().map(|i| i as i32).collect()
+}
+
+/// Sorted data with roughly one random swap per thousand elements.
+pub fn nearly_sorted(n: usize, seed: u64) -> Vec<i32> {
+    let mut data = sorted(n);
+    if n < 2 {
+        return data;
+    }
+    let mut seed = seed;
+    for _ in 0..(n / 1000).max(1) {
+        let i = lcg(&mut seed) as usize % n;
+        let j = lcg(&mut seed) as usize % n;
+        data.swap(i, j);
+    }
+    data
+}
+
+/// `n` copies of the same value.
+pub fn all_equal(n: usize) -> Vec<i32> {
+    vec![42; n]
+}
+
+/// Repeating ascending runs of 4096 values.
+pub fn sawtooth(n: usize) -> Vec<i32> {
+    (0..n).map(|i| (i % 4096) as i32).collect()
+}
@@ -0,0 +1,53 @@
+use core::fmt;
+
+/// Reasons a checked sort refuses its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortError {
+    /// The data pointer was null.
+    NullPointer,
+    /// The data pointer was not aligned for the element type.
+    Misaligned {
+        /// Address of the rejected pointer.
+        addr: usize,
+        /// Alignment the element type requires.
+        align: usize,
+    },
+    /// The length exceeds the largest slice of the element type.
+    TooLong {
+        /// Rejected length.
+        len: usize,
+        /// Largest accepted length.
+        max: usize,
+    },
+}
+
+impl fmt::Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SortError::NullPointer => f.write_str("null data pointer"),
+            SortError::Misaligned { addr, align } => {
+                write!(f, "data pointer {addr:#x} is not {align}-byte aligned")
+            }
+            SortError::TooLong { len, max } => {
+                write!(f, "length {len} exceeds the maximum of {max}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for SortError {}
+
+/// A cancellable sort was stopped before it finished.
+///
+/// The slice then holds every original element exactly once, in an
+/// unspecified order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("sort cancelled")
+    }
+}
+
+impl core::error::Error for Cancelled {}
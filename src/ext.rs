@@ -0,0 +1,60 @@
+use core::cmp::Ordering;
+
+/// Method-call syntax for the WaveSort functions, mirroring the slice
+/// methods of `std`.
+///
+/// ```
+/// use wsort::WaveSortExt;
+///
+/// let mut v = vec![3, 1, 2];
+/// v.wave_sort();
+/// assert_eq!(v, [1, 2, 3]);
+///
+/// v.wave_sort_desc();
+/// assert_eq!(v, [3, 2, 1]);
+///
+/// let mut words = ["bb", "a", "ccc"];
+/// words.wave_sort_by_key(|w| w.len());
+/// assert_eq!(words, ["a", "bb", "ccc"]);
+/// ```
+pub trait WaveSortExt<T> {
+    /// Sorts in place with [`sort_unstable`](crate::sort_unstable).
+    fn wave_sort(&mut self)
+    where
+        T: Ord;
+
+    /// Sorts in place with a comparator, see [`sort_by`](crate::sort_by).
+    fn wave_sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, compare: F);
+
+    /// Sorts in place by a key, see [`sort_by_key`](crate::sort_by_key).
+    fn wave_sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, f: F);
+
+    /// Sorts in place in descending order with [`sort_desc`](crate::sort_desc).
+    fn wave_sort_desc(&mut self)
+    where
+        T: Ord;
+}
+
+impl<T> WaveSortExt<T> for [T] {
+    fn wave_sort(&mut self)
+    where
+        T: Ord,
+    {
+        crate::sort_unstable(self);
+    }
+
+    fn wave_sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, compare: F) {
+        crate::sort_by(self, compare);
+    }
+
+    fn wave_sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, f: F) {
+        crate::sort_by_key(self, f);
+    }
+
+    fn wave_sort_desc(&mut self)
+    where
+        T: Ord,
+    {
+        crate::sort_desc(self);
+    }
+}
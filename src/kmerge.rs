@@ -0,0 +1,22 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::iter;
+
+pub fn kmerge<'a>(
+    sources: &'a mut [&mut dyn Iterator<Item = i32>],
+) -> impl Iterator<Item = i32> + 'a {
+    // Min-heap of each source's head, tagged with its source index so equal
+    // values come out in source order.
+    let mut heads: BinaryHeap<Reverse<(i32, usize)>> = sources
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(i, source)| source.next().map(|x| Reverse((x, i))))
+        .collect();
+    iter::from_fn(move || {
+        let Reverse((x, i)) = heads.pop()?;
+        if let Some(next) = sources[i].next() {
+            heads.push(Reverse((next, i)));
+        }
+        Some(x)
+    })
+}
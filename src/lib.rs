@@ -0,0 +1,786 @@
+//! WaveSort: an in-place, adaptive sorting algorithm with a pure Rust
+//! implementation and a hand-written AMD64 assembly backend.
+//!
+//! Without the default `std` feature the crate is `no_std` and only the pure
+//! Rust sorting functions are available.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::cmp::Ordering;
+use core::ops::Range;
+use core::sync::atomic::AtomicBool;
+
+#[cfg(feature = "std")]
+mod asm;
+#[cfg(feature = "std")]
+mod builder;
+mod config;
+#[cfg(feature = "std")]
+mod counting;
+mod error;
+mod ext;
+#[cfg(feature = "std")]
+mod kmerge;
+#[cfg(feature = "std")]
+mod merge;
+#[cfg(feature = "std")]
+mod parallel;
+#[cfg(feature = "std")]
+mod radix;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd;
+#[cfg(feature = "std")]
+mod sorter;
+mod stats;
+mod wavesort_rust;
+
+#[cfg(feature = "std")]
+pub use asm::{
+    ASM_VARIANT, AsmSortable, AsmTier, HAS_ASM, asm_sort, try_wavesort_asm, try_wavesort_asm_raw,
+    wavesort_asm_safe, wavesort_asm_safe_desc, wavesort_asm_safe_dir, wavesort_asm_safe_i16,
+    wavesort_asm_safe_i64, wavesort_asm_safe_u32, wavesort_asm_tier,
+};
+#[cfg(feature = "std")]
+pub use builder::WaveSort;
+pub use config::{Backend, DEFAULT_INSERTION_THRESHOLD, Partition, SortConfig};
+pub use error::{Cancelled, SortError};
+pub use ext::WaveSortExt;
+#[cfg(feature = "std")]
+pub use sorter::WaveSorter;
+pub use stats::SortStats;
+
+// -----------------------------------------------------------------------------
+// Public API
+// -----------------------------------------------------------------------------
+
+/// Sorts the slice in place using the pure Rust WaveSort.
+///
+/// This is the fast path. Like [`slice::sort_unstable`] it may reorder
+/// equal elements; use [`sort`] when their original order matters.
+///
+/// ```
+/// let mut v = [(2, 'a'), (1, 'b'), (2, 'c')];
+/// wsort::sort_unstable(&mut v);
+/// assert_eq!(v, [(1, 'b'), (2, 'a'), (2, 'c')]);
+/// ```
+pub fn sort_unstable<T: Ord>(arr: &mut [T]) {
+    wavesort_rust::sort(arr);
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+/// [`sort_unstable`] with the wave recursion on the native stack instead of
+/// a heap work stack. Only a reference for tests; not part of the stable
+/// API.
+pub fn sort_recursive<T: Ord>(arr: &mut [T]) {
+    wavesort_rust::sort_recursive(arr, &SortConfig::default());
+}
+
+#[cfg(feature = "std")]
+#[doc(hidden)]
+/// [`sort_recursive`] with custom tuning. Only a reference for tests; not
+/// part of the stable API.
+pub fn sort_recursive_with_config<T: Ord>(arr: &mut [T], cfg: &SortConfig) {
+    wavesort_rust::sort_recursive(arr, cfg);
+}
+
+/// Sorts the slice in place, keeping equal elements in their original order.
+///
+/// Named after [`slice::sort`], which is stable too. WaveSort itself is
+/// unstable, so this uses a bottom-up merge sort instead: short runs are
+/// insertion-sorted and then merged in place with the same rotation merge
+/// as [`merge_sorted`]. It allocates nothing but is slower than
+/// [`sort_unstable`] on large inputs.
+///
+/// Being generic, it compares `i32`s like any other type and never takes
+/// the counting-sort path for narrow value ranges. [`sort_i32`] does (and
+/// is stable too, since equal `i32`s are indistinguishable).
+///
+/// ```
+/// let mut stable = [("b", 2), ("z", 1), ("a", 2)];
+/// wsort::sort(&mut stable);
+/// assert_eq!(stable, [("a", 2), ("b", 2), ("z", 1)]);
+/// ```
+pub fn sort<T: Ord>(arr: &mut [T]) {
+    wavesort_rust::sort_stable_by(arr, DEFAULT_INSERTION_THRESHOLD, &mut T::cmp);
+}
+
+#[cfg(feature = "std")]
+/// Stable merge sort through a buffer, for when stability and a predictable
+/// O(n log n) matter more than sorting in place.
+///
+/// Runs of [`DEFAULT_INSERTION_THRESHOLD`] elements are insertion-sorted,
+/// then merged bottom-up through a buffer of `arr.len() / 2` elements, so
+/// it is usually faster than [`sort`], whose merges rotate in place. Runs
+/// that are already in order are not merged. [`Backend::MergeSort`] selects
+/// it in [`sort_with_config`].
+///
+/// ```
+/// let mut v = vec![5, 1, 4, 1, 3];
+/// wsort::merge_sort(&mut v);
+/// assert_eq!(v, [1, 1, 3, 4, 5]);
+///
+/// let cfg = wsort::SortConfig {
+///     backend: wsort::Backend::MergeSort,
+///     ..Default::default()
+/// };
+/// wsort::sort_with_config(&mut v, &cfg);
+/// ```
+pub fn merge_sort<T: Ord>(arr: &mut [T]) {
+    merge::sort_by(arr, DEFAULT_INSERTION_THRESHOLD, &mut T::cmp);
+}
+
+/// Old name of [`sort`], from before `sort` became the stable sort.
+#[deprecated(note = "renamed to `sort`, which is now stable; use `sort_unstable` for WaveSort")]
+pub fn sort_stable<T: Ord>(arr: &mut [T]) {
+    sort(arr);
+}
+
+/// Sorts the slice in place using the pure Rust WaveSort with custom tuning.
+pub fn sort_with_config<T: Ord>(arr: &mut [T], cfg: &SortConfig) {
+    wavesort_rust::sort_by_config(arr, cfg, T::cmp);
+}
+
+/// Sorts `i32`s in place with the pure Rust WaveSort.
+///
+/// Same result as [`sort_unstable`]. Leaf runs of up to 16 elements go
+/// through the same sorting networks as every WaveSort, applied here with
+/// `min`/`max` instead of the comparator and vectorized with the `simd`
+/// feature on an x86-64 CPU with AVX2. With the
+/// `std` feature, inputs whose values span a range no wider than their
+/// length are counting-sorted instead (see [`sort_counting`]).
+pub fn sort_i32(arr: &mut [i32]) {
+    sort_i32_with_config(arr, &SortConfig::default());
+}
+
+/// Sorts `i32`s in place like [`sort_i32`] with custom tuning.
+///
+/// Unlike [`sort_with_config`] this honors
+/// [`SortConfig::radix_threshold`], switching to [`sort_radix`] for long
+/// slices.
+pub fn sort_i32_with_config(arr: &mut [i32], cfg: &SortConfig) {
+    #[cfg(feature = "std")]
+    if cfg.radix_threshold.is_some_and(|t| arr.len() > t) {
+        radix::sort_i32(arr);
+        return;
+    }
+    #[cfg(feature = "std")]
+    if counting::detect_and_sort(arr) {
+        return;
+    }
+    wave_sort_i32(arr, cfg);
+}
+
+/// The WaveSort fallback of [`sort_i32_with_config`], with the SIMD leaf
+/// sort when it is available.
+fn wave_sort_i32(arr: &mut [i32], cfg: &SortConfig) {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    let small_sort = simd::small_sort_i32();
+    #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+    let small_sort = None;
+    let small_sort = small_sort.or(Some(wavesort_rust::small_sort_network as _));
+    wavesort_rust::sort_counted(arr, cfg, i32::cmp, &mut (), small_sort);
+}
+
+#[cfg(feature = "std")]
+/// Sorts `i32`s whose values all lie in `min..=max` with a counting sort.
+///
+/// Takes O(n + range) time and a counts buffer with one entry per value in
+/// the range. Ranges wider than 2^20 values are sorted with WaveSort
+/// instead of allocating that buffer.
+///
+/// [`sort_i32`], [`sort_i32_with_config`] and [`WaveSorter`] pick this
+/// automatically when the range is no wider than the slice; the generic
+/// sorts such as [`sort`] and [`sort_unstable`] never do.
+///
+/// # Panics
+///
+/// Panics if `min > max` or, when counting, if a value lies outside
+/// `min..=max`.
+pub fn sort_counting(arr: &mut [i32], min: i32, max: i32) {
+    counting::sort_i32(arr, min, max);
+}
+
+#[cfg(feature = "std")]
+/// Sorts `i32`s in place with a four-pass LSD radix sort.
+///
+/// Allocates a scratch buffer as long as `arr`. Each pass scatters on one
+/// byte of the key, with the sign bit flipped so negatives sort first.
+/// Radix sort does O(n) work regardless of the input order, which pays off
+/// over WaveSort on large random inputs but not on presorted ones.
+pub fn sort_radix(arr: &mut [i32]) {
+    radix::sort_i32(arr);
+}
+
+/// Sorts the slice like [`sort_unstable`] and reports how much work it took.
+///
+/// Counts every comparison, every element swap and the deepest nesting of
+/// the wave recursion. [`sort_unstable`] itself carries no counting
+/// overhead.
+pub fn sort_instrumented(arr: &mut [i32]) -> SortStats {
+    let mut stats = SortStats::default();
+    wavesort_rust::sort_counted(arr, &SortConfig::default(), i32::cmp, &mut stats, None);
+    stats
+}
+
+#[cfg(feature = "std")]
+/// Sorts the slice in place, splitting large slices across threads.
+///
+/// The slice is halved recursively, each half sorted with WaveSort on its
+/// own thread, and the halves merged back in place. Uses up to
+/// [`std::thread::available_parallelism`] threads; small slices are sorted
+/// on the calling thread.
+///
+/// With the `rayon` feature the slice is not halved up front. WaveSort runs
+/// on the rayon pool instead, and the two sides of every large downwave
+/// split are sorted with `rayon::join`, so no merge pass is needed. The
+/// result is exactly that of [`sort_with_config`].
+pub fn sort_parallel<T: Ord + Send>(arr: &mut [T]) {
+    sort_parallel_with_config(arr, &SortConfig::default());
+}
+
+#[cfg(feature = "std")]
+/// Sorts the slice in place like [`sort_parallel`] with custom tuning.
+///
+/// [`SortConfig::parallel_threshold`] sets the smallest slice worth handing
+/// to another thread and [`SortConfig::max_threads`] caps the thread count.
+/// With `max_threads == 1` this is exactly [`sort_with_config`].
+pub fn sort_parallel_with_config<T: Ord + Send>(arr: &mut [T], cfg: &SortConfig) {
+    parallel::sort_parallel(arr, cfg);
+}
+
+#[cfg(feature = "std")]
+/// Sorts the slice in place using the assembly WaveSort.
+pub fn sort_asm(arr: &mut [i32]) {
+    wavesort_asm_safe(arr);
+}
+
+/// Sorts `u32`s in place with the pure Rust WaveSort.
+///
+/// Values above `i32::MAX` sort after smaller ones, unlike casting to `i32`.
+/// `wavesort_asm_safe_u32` is the assembly counterpart.
+pub fn sort_u32(arr: &mut [u32]) {
+    sort_unstable(arr);
+}
+
+/// Copies `src` into `dst` without sorting: the floor for any sort timing.
+///
+/// Every sort reads and writes each element at least once, so this one
+/// pass of `memcpy` bandwidth is a lower bound to report next to sort times.
+/// The benchmark binary prints it as "Copy baseline".
+///
+/// # Panics
+///
+/// Panics if the slices differ in length.
+pub fn sort_copy_baseline(src: &[i32], dst: &mut [i32]) {
+    dst.copy_from_slice(src);
+}
+
+/// Sorts bytes in place with a 256-bucket counting sort.
+///
+/// Runs in O(n) with a fixed `[usize; 256]` of counts on the stack, so it
+/// allocates nothing and works without `std`.
+pub fn sort_bytes(arr: &mut [u8]) {
+    let mut counts = [0usize; 256];
+    for &b in arr.iter() {
+        counts[b as usize] += 1;
+    }
+    let mut out = 0;
+    for (byte, &count) in (0..=u8::MAX).zip(counts.iter()) {
+        arr[out..out + count].fill(byte);
+        out += count;
+    }
+}
+
+/// Sorts booleans in place, every `false` before every `true`.
+///
+/// Counts the `false`s and rewrites the slice, in O(n) time with no extra
+/// memory.
+pub fn sort_bools(arr: &mut [bool]) {
+    let falses = arr.iter().filter(|&&b| !b).count();
+    let (lo, hi) = arr.split_at_mut(falses);
+    lo.fill(false);
+    hi.fill(true);
+}
+
+/// Sorts the slice in place in descending order.
+///
+/// The comparison sense is inverted inside the sort itself, so no separate
+/// reversal pass is made.
+pub fn sort_desc<T: Ord>(arr: &mut [T]) {
+    wavesort_rust::sort_desc(arr);
+}
+
+/// Sorts the slice in place with a comparator function.
+///
+/// The comparator must define a total order, as with [`slice::sort_by`].
+/// If it panics, the slice is left in an unspecified order but still holds
+/// every element exactly once.
+pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], compare: F) {
+    wavesort_rust::sort_by(arr, compare);
+}
+
+/// Sorts the slice in place by the key extracted with `f`.
+///
+/// Keys are recomputed on every comparison rather than cached, so the sort
+/// stays in place with O(1) extra memory. Prefer this when `f` is cheap,
+/// and [`sort_by_cached_key`] when it is not.
+pub fn sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &mut [T], mut f: F) {
+    sort_by(arr, |a, b| f(a).cmp(&f(b)));
+}
+
+/// Sorts `f32`s in place by their IEEE 754 total order.
+///
+/// See [`sort_f64`] for how NaNs and signed zeros are placed.
+pub fn sort_f32(arr: &mut [f32]) {
+    sort_by(arr, f32::total_cmp);
+}
+
+/// Sorts `f64`s in place by their IEEE 754 total order.
+///
+/// Negative NaNs come first and positive NaNs last, with `-0.0` placed
+/// before `+0.0`.
+pub fn sort_f64(arr: &mut [f64]) {
+    sort_by(arr, f64::total_cmp);
+}
+
+#[cfg(feature = "std")]
+/// Returns a sorted copy of `arr`, leaving the input untouched.
+pub fn sorted(arr: &[i32]) -> Vec<i32> {
+    sorted_vec(arr)
+}
+
+#[cfg(feature = "std")]
+/// Returns a sorted copy of `arr`, leaving the input untouched.
+///
+/// The copy is sorted in place with [`sort_unstable`].
+pub fn sorted_vec<T: Ord + Clone>(arr: &[T]) -> Vec<T> {
+    let mut out = arr.to_vec();
+    sort_unstable(&mut out);
+    out
+}
+
+#[cfg(feature = "std")]
+/// Sorts `arr` and removes repeated values, returning the new length.
+///
+/// Duplicates are adjacent once sorted, so a single pass compacts the
+/// distinct values to the front and the `Vec` is truncated after them.
+pub fn sort_dedup(arr: &mut Vec<i32>) -> usize {
+    sort_unstable(arr);
+    arr.dedup();
+    arr.len()
+}
+
+#[cfg(feature = "std")]
+/// Sorts `arr` and returns each distinct value with how often it occurs.
+///
+/// The pairs come out in ascending order of value, counted in one scan
+/// over the sorted slice.
+pub fn sort_run_lengths(arr: &mut [i32]) -> Vec<(i32, usize)> {
+    sort_unstable(arr);
+    let mut runs: Vec<(i32, usize)> = Vec::new();
+    for &x in arr.iter() {
+        match runs.last_mut() {
+            Some((v, count)) if *v == x => *count += 1,
+            _ => runs.push((x, 1)),
+        }
+    }
+    runs
+}
+
+/// Rearranges `arr` into a zig-zag wave: `arr[0] <= arr[1] >= arr[2] <= ...`.
+///
+/// Despite the name this is not WaveSort itself, which leaves `arr`
+/// ascending. The slice is sorted and then every pair after the first
+/// element is swapped, which turns a sorted run into the alternating
+/// pattern in O(n) (the "wiggle sort" arrangement).
+pub fn wave_arrange(arr: &mut [i32]) {
+    sort_i32(arr);
+    for pair in arr.get_mut(1..).unwrap_or_default().chunks_exact_mut(2) {
+        pair.swap(0, 1);
+    }
+}
+
+#[cfg(feature = "std")]
+/// Returns the indices that would sort `arr`, leaving `arr` untouched.
+///
+/// `arr[indices[0]]`, `arr[indices[1]]`, ... is ascending, like NumPy's
+/// `argsort`. Equal elements keep their original relative order, so the
+/// result is fully determined by the input. Use it to reorder several
+/// parallel arrays by one of them.
+pub fn sort_indices<T: Ord>(arr: &[T]) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..arr.len()).collect();
+    sort_by_key(&mut indices, |&i| (&arr[i], i));
+    indices
+}
+
+#[cfg(feature = "std")]
+/// Returns each value of `arr` paired with its original index, in ascending
+/// order of value and, among equal values, of index.
+///
+/// Every pair is packed into one `u64`, the value biased to unsigned in the
+/// high half and the index in the low half, so a single sort of plain
+/// integers orders both at once. Slices too long for 32-bit indices sort
+/// `(value, index)` tuples instead, with the same result.
+///
+/// ```
+/// assert_eq!(
+///     wsort::sort_with_positions(&[3, -1, 3, 0]),
+///     [(-1, 1), (0, 3), (3, 0), (3, 2)],
+/// );
+/// ```
+pub fn sort_with_positions(arr: &[i32]) -> Vec<(i32, usize)> {
+    if u32::try_from(arr.len()).is_err() {
+        let mut pairs: Vec<(i32, usize)> = arr.iter().copied().zip(0..).collect();
+        sort_unstable(&mut pairs);
+        return pairs;
+    }
+    // Flipping the sign bit maps i32::MIN..=i32::MAX onto 0..=u32::MAX in order
+    let mut packed: Vec<u64> = (0u64..)
+        .zip(arr)
+        .map(|(i, &v)| (u64::from(v as u32 ^ 0x8000_0000) << 32) | i)
+        .collect();
+    sort_unstable(&mut packed);
+    packed
+        .into_iter()
+        .map(|p| (((p >> 32) as u32 ^ 0x8000_0000) as i32, p as u32 as usize))
+        .collect()
+}
+
+#[cfg(feature = "std")]
+/// Reorders `arr` in place so that the new `arr[i]` is the old
+/// `arr[perm[i]]`.
+///
+/// Follows each cycle of `perm` with swaps and needs O(1) extra space. Since
+/// `perm` is not modified, a cycle is rotated only from its smallest
+/// position, found by walking the cycle from each position until it returns
+/// or reaches a smaller one. That walk is what costs time: O(n log n) on
+/// average for a random permutation, but O(n²) in the worst case, such as a
+/// rotation by one. Applying the result of [`sort_indices`] sorts `arr`.
+///
+/// # Panics
+///
+/// Panics if `perm.len()` differs from `arr.len()`. Debug builds also panic
+/// if `perm` is not a permutation of `0..arr.len()`; release builds then
+/// leave `arr` in an unspecified order, or panic on an index out of bounds.
+pub fn apply_permutation<T>(arr: &mut [T], perm: &[usize]) {
+    let len = arr.len();
+    assert!(
+        perm.len() == len,
+        "apply_permutation perm length {} differs from slice length {len}",
+        perm.len()
+    );
+    debug_assert!(
+        is_permutation(perm),
+        "apply_permutation perm is not a permutation of 0..{len}"
+    );
+    for start in 0..len {
+        // Bounded so that a repeated index cannot loop forever
+        let mut i = perm[start];
+        let mut steps = 1;
+        while i > start && steps < len {
+            i = perm[i];
+            steps += 1;
+        }
+        if i != start {
+            continue;
+        }
+        let mut i = start;
+        while perm[i] != start {
+            let next = perm[i];
+            arr.swap(i, next);
+            i = next;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// `true` if `perm` holds every index of `0..perm.len()` exactly once.
+fn is_permutation(perm: &[usize]) -> bool {
+    let mut seen = vec![false; perm.len()];
+    perm.iter()
+        .all(|&i| i < perm.len() && !core::mem::replace(&mut seen[i], true))
+}
+
+#[cfg(feature = "std")]
+/// [`apply_permutation`] for a permutation it may overwrite: each position
+/// is set to itself once placed, so every cycle is followed once and the
+/// whole reordering takes O(n) time.
+fn apply_permutation_owned<T>(arr: &mut [T], perm: &mut [usize]) {
+    for start in 0..arr.len() {
+        let mut i = start;
+        while perm[i] != start {
+            let next = perm[i];
+            arr.swap(i, next);
+            perm[i] = i;
+            i = next;
+        }
+        perm[i] = i;
+    }
+}
+
+#[cfg(feature = "std")]
+/// Sorts the slice by the key extracted with `f`, calling `f` once per
+/// element.
+///
+/// The keys are computed up front into a `Vec<(K, usize)>` of keys and
+/// original positions, which is sorted and then applied to `arr` by
+/// following the cycles of the permutation. That costs O(n) extra memory for the keys and the
+/// permutation, where [`sort_by_key`] needs none, so prefer this only when
+/// `f` is expensive, such as parsing or allocating. Equal keys keep their
+/// original order.
+pub fn sort_by_cached_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &mut [T], mut f: F) {
+    let mut keys: Vec<(K, usize)> = arr.iter().map(&mut f).zip(0..).collect();
+    sort_unstable(&mut keys);
+    let mut perm: Vec<usize> = keys.into_iter().map(|(_, i)| i).collect();
+    apply_permutation_owned(arr, &mut perm);
+}
+
+/// Returns `true` if `arr` is in ascending order.
+pub fn is_sorted<T: Ord>(arr: &[T]) -> bool {
+    is_sorted_by(arr, T::cmp)
+}
+
+/// Returns `true` if `arr` is ordered according to `compare`.
+pub fn is_sorted_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &[T], mut compare: F) -> bool {
+    arr.windows(2)
+        .all(|w| compare(&w[0], &w[1]) != Ordering::Greater)
+}
+
+/// Returns the length of the longest ascending prefix of `arr`.
+///
+/// Equals `arr.len()` exactly when [`is_sorted`] holds, so after a cancelled
+/// or interrupted sort it reports where the ordered part ends.
+pub fn is_sorted_until<T: Ord>(arr: &[T]) -> usize {
+    arr.windows(2)
+        .position(|w| w[0] > w[1])
+        .map_or(arr.len(), |i| i + 1)
+}
+
+/// Places the `k` smallest elements, in ascending order, in `arr[..k]`.
+///
+/// The order of `arr[k..]` is unspecified. `k` is clamped to `arr.len()`.
+/// The `k`-th element is isolated with a quickselect built on WaveSort's
+/// `partition`, then only the prefix is sorted.
+pub fn partial_sort<T: Ord>(arr: &mut [T], k: usize) {
+    if arr.len() < 2 {
+        return;
+    }
+    let k = k.min(arr.len());
+    if k < arr.len() {
+        wavesort_rust::select_nth_by(arr, k, &mut T::cmp);
+    }
+    sort_unstable(&mut arr[..k]);
+}
+
+/// Sorts only `arr[range]` in place, leaving the rest of `arr` untouched.
+///
+/// Same as [`sort_i32`] on `&mut arr[range]`, but indices stay relative to
+/// the whole slice.
+///
+/// # Panics
+///
+/// Panics if `range` is decreasing or ends past `arr.len()`.
+pub fn sort_range(arr: &mut [i32], range: Range<usize>) {
+    let len = arr.len();
+    assert!(
+        range.start <= range.end && range.end <= len,
+        "sort_range {}..{} out of bounds for slice of length {len}",
+        range.start,
+        range.end
+    );
+    sort_i32(&mut arr[range]);
+}
+
+/// Sorts `i32`s in place like [`sort_unstable`], stopping early once
+/// `cancel` is set.
+///
+/// The flag is checked before every wave of the recursion, so the sort
+/// stops once the wave in progress is done; a wave costs at most a pass over
+/// the range it covers. It then returns [`Cancelled`] and leaves `arr`
+/// partially sorted, still holding every element exactly once. A flag that
+/// is raised only after the last wave has no effect.
+///
+/// ```
+/// use std::sync::atomic::AtomicBool;
+///
+/// let mut v = vec![3, 1, 2];
+/// assert_eq!(wsort::sort_cancellable(&mut v, &AtomicBool::new(false)), Ok(()));
+/// assert_eq!(v, [1, 2, 3]);
+/// ```
+pub fn sort_cancellable(arr: &mut [i32], cancel: &AtomicBool) -> Result<(), Cancelled> {
+    if wavesort_rust::sort_cancellable(arr, i32::cmp, cancel) {
+        Ok(())
+    } else {
+        Err(Cancelled)
+    }
+}
+
+/// Sorts `i32`s in place with a binary-heap sort.
+///
+/// Takes O(n log n) time with about 2n log2(n) comparisons at most on every
+/// input, and no extra memory. WaveSort is faster on nearly all inputs and
+/// adapts to presorted ones, which heapsort does not; this is the same
+/// heapsort it falls back to when its recursion gets too deep.
+pub fn heapsort(arr: &mut [i32]) {
+    wavesort_rust::heapsort(arr, &mut i32::cmp, &mut ());
+}
+
+/// Reorders `arr` so that `arr[n]` holds the value it would have after a
+/// full sort, and returns that value.
+///
+/// Every element before index `n` is `<=` it and every element after is `>=`.
+///
+/// # Panics
+///
+/// Panics if `n >= arr.len()`.
+pub fn select_nth<T: Ord + Copy>(arr: &mut [T], n: usize) -> T {
+    let len = arr.len();
+    assert!(
+        n < len,
+        "select_nth index {n} out of range for slice of length {len}"
+    );
+    wavesort_rust::select_nth_by(arr, n, &mut T::cmp);
+    arr[n]
+}
+
+/// Returns the `k`-th smallest value of `arr`, counting from 0.
+///
+/// The `i32` counterpart of [`select_nth`]: a quickselect over WaveSort's
+/// `partition` in expected O(n) time, which leaves `arr` partitioned
+/// around index `k`.
+///
+/// # Panics
+///
+/// Panics if `k >= arr.len()`.
+pub fn nth_smallest(arr: &mut [i32], k: usize) -> i32 {
+    let len = arr.len();
+    assert!(
+        k < len,
+        "nth_smallest index {k} out of range for slice of length {len}"
+    );
+    wavesort_rust::select_nth_by(arr, k, &mut i32::cmp);
+    arr[k]
+}
+
+/// Returns the smallest and largest value of `arr`, or `None` if it is
+/// empty.
+///
+/// Scans the elements in pairs: the smaller of each pair is only compared
+/// with the minimum and the larger only with the maximum, so this takes
+/// about 1.5n comparisons instead of 2n.
+pub fn min_max(arr: &[i32]) -> Option<(i32, i32)> {
+    let (&first, rest) = arr.split_first()?;
+    let (mut lo, mut hi) = (first, first);
+    let mut pairs = rest.chunks_exact(2);
+    for pair in &mut pairs {
+        let (small, large) = if pair[0] <= pair[1] {
+            (pair[0], pair[1])
+        } else {
+            (pair[1], pair[0])
+        };
+        lo = lo.min(small);
+        hi = hi.max(large);
+    }
+    if let &[last] = pairs.remainder() {
+        lo = lo.min(last);
+        hi = hi.max(last);
+    }
+    Some((lo, hi))
+}
+
+/// Partitions `arr` around the element at `pivot_index` and returns the
+/// pivot's new index.
+///
+/// Afterwards `arr[index]` is the pivot, every element before it is `<=`
+/// the pivot and every element after it is `>=` it; in particular all
+/// elements `<` the pivot come before `index`. Elements equal to the pivot
+/// may land on either side. This is the Hoare partition WaveSort itself
+/// uses, handy as a building block for selection algorithms.
+///
+/// # Panics
+///
+/// Panics if `pivot_index >= arr.len()`.
+pub fn partition<T: Ord>(arr: &mut [T], pivot_index: usize) -> usize {
+    let len = arr.len();
+    assert!(
+        pivot_index < len,
+        "partition pivot_index {pivot_index} out of range for slice of length {len}"
+    );
+    wavesort_rust::partition_at(arr, pivot_index, &mut T::cmp)
+}
+
+/// Merges the sorted runs `arr[..mid]` and `arr[mid..]` in place.
+///
+/// Uses the same rotation (`block_swap`) machinery as WaveSort, so no
+/// buffer is allocated. Handy for combining separately sorted chunks.
+///
+/// # Panics
+///
+/// Panics if `mid > arr.len()`.
+pub fn merge_sorted<T: Ord>(arr: &mut [T], mid: usize) {
+    let len = arr.len();
+    assert!(
+        mid <= len,
+        "merge_sorted mid {mid} out of range for slice of length {len}"
+    );
+    wavesort_rust::merge_by(arr, mid, &mut T::cmp);
+}
+
+#[cfg(feature = "std")]
+/// Merges any number of ascending `i32` streams into one ascending stream.
+///
+/// Keeps the current head of every source in a binary heap, so each item
+/// costs O(log k) for `k` sources and only `k` values are buffered. This is
+/// the final pass of an external sort: sort chunks that fit in memory with
+/// [`sort_unstable`], spill them, then merge the spilled runs. Equal values
+/// are yielded in source order.
+///
+/// Sources are pulled lazily; each must itself be ascending or the output
+/// will not be.
+pub fn kmerge<'a>(
+    sources: &'a mut [&mut dyn Iterator<Item = i32>],
+) -> impl Iterator<Item = i32> + 'a {
+    kmerge::kmerge(sources)
+}
+
+/// Merges the sorted runs `arr[..left_len]` and `arr[left_len..total_len]`
+/// in place, leaving `arr[total_len..]` untouched.
+///
+/// This is WaveSort's rotation merge: both runs are cut around a
+/// binary-searched split point and the middle pieces are exchanged with
+/// `block_swap`, the same `rotate_left` based rotation the wave recursion
+/// uses, before recursing on each side. It needs O(log n) stack and no
+/// buffer, and keeps equal elements in order.
+///
+/// # Panics
+///
+/// Panics unless `left_len <= total_len <= arr.len()`.
+pub fn rotate_merge<T: Ord>(arr: &mut [T], left_len: usize, total_len: usize) {
+    let len = arr.len();
+    assert!(
+        left_len <= total_len && total_len <= len,
+        "rotate_merge needs left_len {left_len} <= total_len {total_len} <= slice length {len}"
+    );
+    wavesort_rust::merge_by(&mut arr[..total_len], left_len, &mut T::cmp);
+}
+
+// -----------------------------------------------------------------------------
+// C ABI
+// -----------------------------------------------------------------------------
+
+/// Sorts `len` `i32`s starting at `arr` with the pure Rust WaveSort.
+///
+/// C entry point mirroring the assembly `wave_sort`, declared in
+/// `ffi/include/wavesort.h`. A null `arr` or `len < 2` is a no-op.
+///
+/// # Safety
+///
+/// Unless null, `arr` must be valid for reads and writes of `len` `i32`s.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wave_sort_rust(arr: *mut i32, len: usize) {
+    if arr.is_null() || len < 2 {
+        return;
+    }
+    sort_unstable(unsafe { core::slice::from_raw_parts_mut(arr, len) });
+}
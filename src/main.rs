@@ -1,237 +1,524 @@
+#[cfg(feature = "track-alloc")]
+mod alloc_track;
+mod datagen;
+
+use std::fs;
 use std::io;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, Instant};
+
+use datagen::Dist;
+
+const USAGE: &str = "Usage: wsort [--n <count>] [--seed <value>] [--dist <name>] [--format text|json]
+       wsort [--seed <value>] [--dist <name>] --sweep
+       wsort [--n <count>] [--seed <value>] [--dist <name>] [--format text|json] --repeat <k>
+       wsort --input <file> [--backend rust|asm|std|std-unstable] [--output <file>] [--format text|json]";
 
-// -----------------------------------------------------------------------------
-// 1. FFI Declaration (The Wiring)
-// -----------------------------------------------------------------------------
-unsafe extern "C" {
-    /// The function defined in wavesort.asm
-    /// Signature: void wavesort(int32_t *arr, size_t len);
-    fn wave_sort(arr: *mut i32, len: usize);
+/// Sizes benchmarked by `--sweep`, one CSV row each.
+const SWEEP_SIZES: [usize; 5] = [10_000, 100_000, 1_000_000, 10_000_000, 100_000_000];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    /// A single JSON object on stdout, for feeding into dashboards.
+    Json,
 }
 
-// Safe Rust Wrapper for the ASM function
-pub fn wavesort_asm_safe(arr: &mut [i32]) {
-    unsafe {
-        wave_sort(arr.as_mut_ptr(), arr.len());
-    }
+/// Implementation `--backend` picks for sorting an `--input` file.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SortImpl {
+    Rust,
+    Asm,
+    Std,
+    StdUnstable,
 }
 
-// -----------------------------------------------------------------------------
-// 2. Pure Rust Implementation (For Comparison)
-// -----------------------------------------------------------------------------
-mod wavesort_rust {
-    const INSERTION_THRESHOLD: usize = 32;
+impl SortImpl {
+    const NAMES: &str = "rust, asm, std, std-unstable";
 
-    pub fn sort(arr: &mut [i32]) {
-        let n = arr.len();
-        if n < 2 {
-            return;
-        }
-        if n <= INSERTION_THRESHOLD {
-            insertion_sort(arr);
-            return;
+    fn parse(name: &str) -> Option<SortImpl> {
+        match name {
+            "rust" => Some(SortImpl::Rust),
+            "asm" => Some(SortImpl::Asm),
+            "std" => Some(SortImpl::Std),
+            "std-unstable" => Some(SortImpl::StdUnstable),
+            _ => None,
         }
-        upwave(arr, 0, n - 1);
     }
 
-    fn insertion_sort(arr: &mut [i32]) {
-        let len = arr.len();
-        if len < 2 {
-            return;
-        }
-        for i in 1..len {
-            unsafe {
-                let key = *arr.get_unchecked(i);
-                let mut j = i;
-                while j > 0 && *arr.get_unchecked(j - 1) > key {
-                    *arr.get_unchecked_mut(j) = *arr.get_unchecked(j - 1);
-                    j -= 1;
-                }
-                *arr.get_unchecked_mut(j) = key;
-            }
+    fn name(self) -> &'static str {
+        match self {
+            SortImpl::Rust => "rust",
+            SortImpl::Asm => "asm",
+            SortImpl::Std => "std",
+            SortImpl::StdUnstable => "std-unstable",
         }
     }
 
-    #[inline(always)]
-    fn block_swap(arr: &mut [i32], m: usize, r: usize, p: usize) {
-        let left_len = r.wrapping_sub(m);
-        if left_len == 0 {
-            return;
-        }
-        let range_len = p - m + 1;
-        arr[m..m + range_len].rotate_left(left_len);
-    }
-
-    fn partition(arr: &mut [i32], l: usize, r: usize, p_idx: usize) -> usize {
-        unsafe {
-            let ptr = arr.as_mut_ptr();
-            let pivot_val = *ptr.add(p_idx);
-            let mut i = l;
-            let mut j = r;
-            loop {
-                loop {
-                    let val = *ptr.add(i);
-                    if val >= pivot_val {
-                        break;
-                    }
-                    i += 1;
-                    if i == j {
-                        return i;
-                    }
-                }
-                loop {
-                    if j == i {
-                        return i;
-                    }
-                    j -= 1;
-                    let val = *ptr.add(j);
-                    if val <= pivot_val {
-                        break;
-                    }
-                }
-                std::ptr::swap(ptr.add(i), ptr.add(j));
-            }
+    /// Every implementation, in the order the benchmark reports them.
+    const ALL: [SortImpl; 4] = [
+        SortImpl::Rust,
+        SortImpl::Asm,
+        SortImpl::Std,
+        SortImpl::StdUnstable,
+    ];
+
+    fn sort(self, data: &mut [i32]) {
+        match self {
+            SortImpl::Rust => wsort::sort_unstable(data),
+            SortImpl::Asm => wsort::sort_asm(data),
+            SortImpl::Std => data.sort(),
+            SortImpl::StdUnstable => data.sort_unstable(),
         }
     }
+}
 
-    fn downwave(arr: &mut [i32], start: usize, sorted_start: usize, end: usize) {
-        if sorted_start == start {
-            return;
-        }
-        if end - start <= INSERTION_THRESHOLD {
-            insertion_sort(&mut arr[start..=end]);
-            return;
-        }
-        let p = sorted_start + (end - sorted_start) / 2;
-        let m = partition(arr, start, sorted_start, p);
-        if m == sorted_start {
-            if p == sorted_start {
-                if sorted_start > 0 {
-                    upwave(arr, start, sorted_start - 1);
-                }
-                return;
+/// Benchmark parameters; the defaults match the published numbers.
+struct Args {
+    n: usize,
+    seed: u64,
+    dist: Dist,
+    format: Format,
+    /// Run every size in `SWEEP_SIZES` and print CSV instead of `n` alone.
+    sweep: bool,
+    /// Sort this file of little-endian `i32`s instead of generated data.
+    input: Option<PathBuf>,
+    /// Where to write the sorted `input`, in the same format.
+    output: Option<PathBuf>,
+    /// Implementation that sorts `input`.
+    backend: SortImpl,
+    /// Time every implementation this many times and report min/median/max.
+    repeat: Option<usize>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        n: 100_000_000,
+        seed: 1,
+        dist: Dist::Random,
+        format: Format::Text,
+        sweep: false,
+        input: None,
+        output: None,
+        backend: SortImpl::Rust,
+        repeat: None,
+    };
+    let mut it = std::env::args().skip(1);
+    while let Some(flag) = it.next() {
+        let mut value = |name: &str| {
+            it.next()
+                .ok_or_else(|| format!("missing value for `{name}`"))
+        };
+        match flag.as_str() {
+            "--n" => {
+                let v = value("--n")?;
+                args.n = v.parse().map_err(|e| format!("invalid --n `{v}`: {e}"))?;
             }
-            if p > 0 {
-                downwave(arr, start, sorted_start, p - 1);
+            "--seed" => {
+                let v = value("--seed")?;
+                args.seed = v
+                    .parse()
+                    .map_err(|e| format!("invalid --seed `{v}`: {e}"))?;
             }
-            return;
-        }
-        block_swap(arr, m, sorted_start, p);
-        if m == start {
-            if p == sorted_start {
-                upwave(arr, m + 1, end);
-                return;
+            "--dist" => {
+                let v = value("--dist")?;
+                args.dist = Dist::parse(&v).ok_or_else(|| {
+                    format!(
+                        "unknown distribution `{v}` (expected one of {})",
+                        Dist::NAMES
+                    )
+                })?;
             }
-            let p_next = p + 1;
-            downwave(arr, m + p_next - sorted_start, p_next, end);
-            return;
-        }
-        if p == sorted_start {
-            if m > 0 {
-                upwave(arr, start, m - 1);
+            "--format" => {
+                args.format = match value("--format")?.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown format `{other}`")),
+                };
             }
-            upwave(arr, m + 1, end);
-            return;
-        }
-        let right_part_len = p - sorted_start;
-        let split_point = m + right_part_len;
-        if split_point > 0 {
-            downwave(arr, start, m, split_point - 1);
+            "--sweep" => args.sweep = true,
+            "--input" => args.input = Some(value("--input")?.into()),
+            "--output" => args.output = Some(value("--output")?.into()),
+            "--backend" => {
+                let v = value("--backend")?;
+                args.backend = SortImpl::parse(&v).ok_or_else(|| {
+                    format!(
+                        "unknown backend `{v}` (expected one of {})",
+                        SortImpl::NAMES
+                    )
+                })?;
+            }
+            "--repeat" => {
+                let v = value("--repeat")?;
+                let k = v
+                    .parse()
+                    .map_err(|e| format!("invalid --repeat `{v}`: {e}"))?;
+                if k == 0 {
+                    return Err("`--repeat` must be at least 1".into());
+                }
+                args.repeat = Some(k);
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                process::exit(0);
+            }
+            other => return Err(format!("unknown argument `{other}`")),
         }
-        downwave(arr, split_point + 1, p + 1, end);
     }
+    if args.input.is_none() && args.output.is_some() {
+        return Err("`--output` needs `--input`".into());
+    }
+    if args.input.is_some() && args.sweep {
+        return Err("`--input` and `--sweep` cannot be combined".into());
+    }
+    if args.sweep && args.format == Format::Json {
+        return Err("`--sweep` prints CSV and cannot be combined with `--format json`".into());
+    }
+    if args.repeat.is_some() && (args.input.is_some() || args.sweep) {
+        return Err("`--repeat` cannot be combined with `--input` or `--sweep`".into());
+    }
+    Ok(args)
+}
+
+/// Reads a file of little-endian `i32`s.
+fn read_i32s(path: &Path) -> io::Result<Vec<i32>> {
+    let bytes = fs::read(path)?;
+    if bytes.len() % 4 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("length {} is not a multiple of 4", bytes.len()),
+        ));
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+/// Writes `data` as little-endian `i32`s, the format `read_i32s` reads.
+fn write_i32s(path: &Path, data: &[i32]) -> io::Result<()> {
+    let bytes: Vec<u8> = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+    fs::write(path, bytes)
+}
 
-    fn upwave(arr: &mut [i32], start: usize, end: usize) {
-        if start == end {
-            return;
+/// Sorts the `--input` file with `--backend`, reports the time and writes
+/// the result to `--output` if given. Returns whether the output is sorted.
+fn sort_file(input: &Path, args: &Args) -> io::Result<bool> {
+    let with_path =
+        |path: &Path, e: io::Error| io::Error::new(e.kind(), format!("{}: {e}", path.display()));
+    let mut data = read_i32s(input).map_err(|e| with_path(input, e))?;
+    let (dur, allocated) = measure(|| args.backend.sort(&mut data));
+    match args.format {
+        Format::Text => {
+            println!(
+                "Sorted {} integers from {} with {}: {:.6} s",
+                data.len(),
+                input.display(),
+                args.backend.name(),
+                dur.as_secs_f64()
+            );
+            print_allocated(allocated);
         }
-        if end - start <= INSERTION_THRESHOLD {
-            insertion_sort(&mut arr[start..=end]);
-            return;
+        Format::Json => println!(
+            "{{\"input\":{},\"n\":{},\"backend\":\"{}\",\"secs\":{:.6}}}",
+            json_string(&input.to_string_lossy()),
+            data.len(),
+            args.backend.name(),
+            dur.as_secs_f64()
+        ),
+    }
+    let sorted = wsort::is_sorted(&data);
+    if !sorted {
+        eprintln!("FAILURE: {} sort failed.", args.backend.name());
+    }
+    if let Some(output) = &args.output {
+        write_i32s(output, &data).map_err(|e| with_path(output, e))?;
+    }
+    Ok(sorted)
+}
+
+/// Quotes `s` as a JSON string. Control characters become `\uXXXX`.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
         }
-        let mut sorted_start = end;
-        let mut sorted_len;
-        if end == 0 {
-            return;
+    }
+    out.push('"');
+    out
+}
+
+/// Runs `sort`, returning its wall time and, with the `track-alloc` feature,
+/// the bytes it allocated.
+fn measure(sort: impl FnOnce()) -> (Duration, Option<usize>) {
+    #[cfg(feature = "track-alloc")]
+    let before = alloc_track::allocated();
+    let start = Instant::now();
+    sort();
+    let dur = start.elapsed();
+    #[cfg(feature = "track-alloc")]
+    let allocated = Some(alloc_track::allocated() - before);
+    #[cfg(not(feature = "track-alloc"))]
+    let allocated = None;
+    (dur, allocated)
+}
+
+fn print_allocated(allocated: Option<usize>) {
+    if let Some(bytes) = allocated {
+        println!("  allocations during sort: {bytes} bytes");
+    }
+}
+
+/// Spread of the wall times of repeated runs of one sort.
+struct Stats {
+    min: Duration,
+    median: Duration,
+    max: Duration,
+    /// Whether every run left its copy of the data sorted.
+    sorted: bool,
+}
+
+/// Sorts a fresh clone of `data` with `f` `k` times and summarizes the
+/// times. Cloning happens outside the timed region.
+fn time_sort<F: FnMut(&mut [i32])>(data: &[i32], k: usize, mut f: F) -> Stats {
+    assert!(k > 0, "time_sort needs at least one run");
+    let mut times = Vec::with_capacity(k);
+    let mut sorted = true;
+    for _ in 0..k {
+        let mut copy = data.to_vec();
+        let (dur, _) = measure(|| f(&mut copy));
+        times.push(dur);
+        sorted &= wsort::is_sorted(&copy);
+    }
+    times.sort_unstable();
+    let median = if k % 2 == 1 {
+        times[k / 2]
+    } else {
+        (times[k / 2 - 1] + times[k / 2]) / 2
+    };
+    Stats {
+        min: times[0],
+        median,
+        max: times[k - 1],
+        sorted,
+    }
+}
+
+/// `--repeat` mode: times every implementation `k` times on the same data
+/// and prints min/median/max seconds. Returns the number of failures.
+fn run_repeated(n: usize, seed: u64, dist: Dist, k: usize, format: Format) -> usize {
+    let data = dist.generate(n, seed);
+    let mut failures = 0;
+    let mut json = Vec::new();
+    if format == Format::Text {
+        println!("Timing {n} integer samples, {k} runs per sort (min / median / max):");
+    }
+    for backend in SortImpl::ALL {
+        let stats = time_sort(&data, k, |d| backend.sort(d));
+        if !stats.sorted {
+            eprintln!("FAILURE: {} sort failed.", backend.name());
+            failures += 1;
         }
-        let mut left_bound = end - 1;
-        let total_len = end - start + 1;
-        loop {
-            downwave(arr, left_bound, sorted_start, end);
-            sorted_start = left_bound;
-            sorted_len = end - sorted_start + 1;
-            if total_len < (sorted_len << 2) {
-                break;
-            }
-            let next_expansion = (sorted_len << 1) + 1;
-            if end < next_expansion || (end - next_expansion) < start {
-                left_bound = start;
-            } else {
-                left_bound = end - next_expansion;
-            }
-            if left_bound < start {
-                left_bound = start;
-            }
-            if sorted_start == start {
-                break;
-            }
+        match format {
+            Format::Text => println!(
+                "{:<13} {:.6} s / {:.6} s / {:.6} s",
+                format!("{}:", backend.name()),
+                stats.min.as_secs_f64(),
+                stats.median.as_secs_f64(),
+                stats.max.as_secs_f64()
+            ),
+            Format::Json => json.push(format!(
+                "\"{}\":{{\"min_secs\":{:.6},\"median_secs\":{:.6},\"max_secs\":{:.6}}}",
+                backend.name(),
+                stats.min.as_secs_f64(),
+                stats.median.as_secs_f64(),
+                stats.max.as_secs_f64()
+            )),
         }
-        downwave(arr, start, sorted_start, end);
     }
+    if format == Format::Json {
+        println!(
+            "{{\"n\":{},\"seed\":{},\"dist\":\"{}\",\"repeat\":{},{}}}",
+            n,
+            seed,
+            dist.name(),
+            k,
+            json.join(",")
+        );
+    }
+    failures
 }
 
-fn main() -> io::Result<()> {
-    const N: usize = 100_000_000;
-    println!("Initializing benchmark for {} integer samples...", N);
+/// Wall times of one benchmark run.
+struct Timings {
+    /// Copying the input once, the floor under every sort time.
+    copy: Duration,
+    rust: Duration,
+    asm: Duration,
+    std: Duration,
+    std_unstable: Duration,
+    /// Implementations whose output was not sorted.
+    failures: usize,
+}
 
-    // Generate random data
-    let mut data_asm = Vec::with_capacity(N);
-    let mut seed: u64 = 1;
-    for _ in 0..N {
-        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
-        let val = (seed / 65536) % 2147483648;
-        data_asm.push(val as i32);
+/// Sorts one `dist` data set of `n` values with every implementation and
+/// verifies the results, printing progress as it goes when `text` is set.
+fn run(n: usize, seed: u64, dist: Dist, text: bool) -> Timings {
+    if text {
+        println!("Initializing benchmark for {} integer samples...", n);
     }
 
+    // Generate random data
+    let mut data_asm = dist.generate(n, seed);
+
     // Clone for fair comparison
     let mut data_rust = data_asm.clone();
     let mut data_std = data_asm.clone();
+    let mut data_std_unstable = data_asm.clone();
 
-    println!("Data generated. Starting benchmark...\n");
+    if text {
+        println!("Data generated. Starting benchmark...\n");
+    }
+
+    // --- Copy baseline ---
+    // Both buffers are already paged in and hold the same values, so this
+    // times the copy alone and leaves `data_rust` unchanged.
+    let (dur_copy, _) = measure(|| wsort::sort_copy_baseline(&data_asm, &mut data_rust));
+    if text {
+        println!("Copy baseline: {:.6} s", dur_copy.as_secs_f64());
+    }
 
     // --- Rust WaveSort ---
-    let start_rust = Instant::now();
-    wavesort_rust::sort(&mut data_rust);
-    let dur_rust = start_rust.elapsed();
-    println!("Rust WaveSort: {:.6} s", dur_rust.as_secs_f64());
+    let (dur_rust, alloc_rust) = measure(|| wsort::sort_unstable(&mut data_rust));
+    if text {
+        println!("Rust WaveSort: {:.6} s", dur_rust.as_secs_f64());
+        print_allocated(alloc_rust);
+    }
 
     // --- ASM WaveSort ---
-    let start_asm = Instant::now();
-    wavesort_asm_safe(&mut data_asm);
-    let dur_asm = start_asm.elapsed();
-    println!("ASM  WaveSort: {:.6} s", dur_asm.as_secs_f64());
+    let (dur_asm, alloc_asm) = measure(|| wsort::sort_asm(&mut data_asm));
+    if text {
+        println!("ASM  WaveSort: {:.6} s", dur_asm.as_secs_f64());
+        print_allocated(alloc_asm);
+    }
 
     // --- Standard Lib ---
-    let start_std = Instant::now();
-    data_std.sort();
-    let dur_std = start_std.elapsed();
-    println!("Std  Sort:     {:.6} s", dur_std.as_secs_f64());
+    let (dur_std, alloc_std) = measure(|| data_std.sort());
+    if text {
+        println!("Std  Sort:     {:.6} s", dur_std.as_secs_f64());
+        print_allocated(alloc_std);
+    }
+
+    // --- Standard Lib (unstable) ---
+    let (dur_std_unstable, alloc_std_unstable) = measure(|| data_std_unstable.sort_unstable());
+    if text {
+        println!("Std  Unstable: {:.6} s", dur_std_unstable.as_secs_f64());
+        print_allocated(alloc_std_unstable);
+    }
 
     // --- Verification ---
-    if !is_sorted(&data_rust) {
-        eprintln!("FAILURE: Rust WaveSort failed.");
+    let mut failures = 0;
+    for (name, data) in [
+        ("Rust WaveSort", &data_rust),
+        ("ASM WaveSort", &data_asm),
+        ("Std Sort", &data_std),
+        ("Std Unstable Sort", &data_std_unstable),
+    ] {
+        if !wsort::is_sorted(data) {
+            eprintln!("FAILURE: {name} failed.");
+            failures += 1;
+        }
+    }
+
+    Timings {
+        copy: dur_copy,
+        rust: dur_rust,
+        asm: dur_asm,
+        std: dur_std,
+        std_unstable: dur_std_unstable,
+        failures,
+    }
+}
+
+fn main() -> io::Result<()> {
+    let args = parse_args().unwrap_or_else(|msg| {
+        eprintln!("error: {msg}\n{USAGE}");
+        process::exit(2);
+    });
+
+    if let Some(input) = &args.input {
+        let sorted = sort_file(input, &args).unwrap_or_else(|e| {
+            eprintln!("error: {e}");
+            process::exit(1);
+        });
+        exit_on_failure(usize::from(!sorted));
+        return Ok(());
     }
-    if !is_sorted(&data_asm) {
-        eprintln!("FAILURE: ASM WaveSort failed.");
+
+    let Args {
+        n,
+        seed,
+        dist,
+        format,
+        sweep,
+        repeat,
+        ..
+    } = args;
+
+    if let Some(k) = repeat {
+        exit_on_failure(run_repeated(n, seed, dist, k, format));
+        return Ok(());
     }
-    if !is_sorted(&data_std) {
-        eprintln!("FAILURE: Std Sort failed.");
+
+    if sweep {
+        // Every size still runs after a failure, so the output stays complete
+        let mut failures = 0;
+        println!("n,rust,asm,std,copy");
+        for n in SWEEP_SIZES {
+            let t = run(n, seed, dist, false);
+            failures += t.failures;
+            println!(
+                "{},{:.6},{:.6},{:.6},{:.6}",
+                n,
+                t.rust.as_secs_f64(),
+                t.asm.as_secs_f64(),
+                t.std.as_secs_f64(),
+                t.copy.as_secs_f64()
+            );
+        }
+        exit_on_failure(failures);
+        return Ok(());
     }
 
+    let t = run(n, seed, dist, format == Format::Text);
+    if format == Format::Json {
+        println!(
+            "{{\"n\":{},\"seed\":{},\"dist\":\"{}\",\"rust_secs\":{:.6},\"asm_secs\":{:.6},\"std_secs\":{:.6},\"std_unstable_secs\":{:.6},\"copy_secs\":{:.6}}}",
+            n,
+            seed,
+            dist.name(),
+            t.rust.as_secs_f64(),
+            t.asm.as_secs_f64(),
+            t.std.as_secs_f64(),
+            t.std_unstable.as_secs_f64(),
+            t.copy.as_secs_f64()
+        );
+    }
+    exit_on_failure(t.failures);
+
     Ok(())
 }
 
-fn is_sorted(arr: &[i32]) -> bool {
-    arr.windows(2).all(|w| w[0] <= w[1])
+/// Exits with status 1 if any sort produced unsorted output, so the
+/// benchmark doubles as a smoke test. The failures are already on stderr.
+fn exit_on_failure(failures: usize) {
+    if failures > 0 {
+        process::exit(1);
+    }
 }
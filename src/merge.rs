@@ -0,0 +1,121 @@
+use core::cmp::Ordering;
+use core::mem;
+use core::ptr;
+
+use crate::wavesort_rust::insertion_sort;
+
+/// Buffered stable merge sort: insertion-sorts runs of `threshold` elements,
+/// then merges neighbouring runs bottom-up through a buffer of `len / 2`
+/// elements. Unlike `wavesort_rust::sort_stable_by`, each merge is linear,
+/// so the whole sort is O(n log n).
+pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], threshold: usize, compare: &mut F) {
+    let n = arr.len();
+    // Zero-sized values are indistinguishable, so any order is sorted
+    if mem::size_of::<T>() == 0 {
+        return;
+    }
+    let run = threshold.max(1);
+    for chunk in arr.chunks_mut(run) {
+        insertion_sort(chunk, compare, &mut ());
+    }
+    if n <= run {
+        return;
+    }
+    // Never holds initialized elements, so dropping it drops none
+    let mut buf: Vec<T> = Vec::with_capacity(n / 2);
+    let mut width = run;
+    while width < n {
+        for chunk in arr.chunks_mut(2 * width) {
+            // Runs already in order, as in presorted input, need no merge
+            if chunk.len() > width && compare(&chunk[width - 1], &chunk[width]) == Ordering::Greater
+            {
+                unsafe { merge(chunk, width, buf.as_mut_ptr(), compare) };
+            }
+        }
+        width = width.saturating_mul(2);
+    }
+}
+
+/// Merges the sorted runs `v[..mid]` and `v[mid..]`, moving the shorter run
+/// out to `buf` first. On ties the element from the left run goes first.
+///
+/// # Safety
+///
+/// `buf` must have room for `min(mid, v.len() - mid)` elements and must not
+/// overlap `v`.
+unsafe fn merge<T, F: FnMut(&T, &T) -> Ordering>(
+    v: &mut [T],
+    mid: usize,
+    buf: *mut T,
+    compare: &mut F,
+) {
+    let len = v.len();
+    let v = v.as_mut_ptr();
+    unsafe {
+        let v_mid = v.add(mid);
+        let v_end = v.add(len);
+        if mid <= len - mid {
+            // Left run into the buffer, then fill the slice front to back.
+            // The gap `dest..right` always holds exactly the buffered count.
+            ptr::copy_nonoverlapping(v, buf, mid);
+            let mut hole = MergeHole {
+                start: buf,
+                end: buf.add(mid),
+                dest: v,
+            };
+            let mut right = v_mid;
+            while hole.start < hole.end && right < v_end {
+                let src = if compare(&*right, &*hole.start) == Ordering::Less {
+                    let src = right;
+                    right = right.add(1);
+                    src
+                } else {
+                    let src = hole.start;
+                    hole.start = src.add(1);
+                    src
+                };
+                ptr::copy_nonoverlapping(src, hole.dest, 1);
+                hole.dest = hole.dest.add(1);
+            }
+        } else {
+            // Right run into the buffer, then fill the slice back to front.
+            // The gap `dest..out` always holds exactly the buffered count.
+            ptr::copy_nonoverlapping(v_mid, buf, len - mid);
+            let mut hole = MergeHole {
+                start: buf,
+                end: buf.add(len - mid),
+                dest: v_mid,
+            };
+            let mut out = v_end;
+            while v < hole.dest && buf < hole.end {
+                out = out.sub(1);
+                let src = if compare(&*hole.end.sub(1), &*hole.dest.sub(1)) == Ordering::Less {
+                    hole.dest = hole.dest.sub(1);
+                    hole.dest
+                } else {
+                    hole.end = hole.end.sub(1);
+                    hole.end
+                };
+                ptr::copy_nonoverlapping(src, out, 1);
+            }
+        }
+        // Dropping `hole` moves whatever is still buffered into the gap
+    }
+}
+
+/// Moves the buffered elements `start..end` to `dest` when dropped, which
+/// finishes a merge normally and restores the slice if a comparison panics.
+struct MergeHole<T> {
+    start: *mut T,
+    end: *mut T,
+    dest: *mut T,
+}
+
+impl<T> Drop for MergeHole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let len = self.end.offset_from_unsigned(self.start);
+            ptr::copy_nonoverlapping(self.start, self.dest, len);
+        }
+    }
+}
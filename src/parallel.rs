@@ -0,0 +1,87 @@
+use core::cmp::Ordering;
+use std::thread;
+
+#[cfg(feature = "rayon")]
+use crate::Backend;
+use crate::SortConfig;
+use crate::wavesort_rust;
+
+pub fn sort_parallel<T: Ord + Send>(arr: &mut [T], cfg: &SortConfig) {
+    sort_parallel_by(arr, cfg, &T::cmp);
+}
+
+/// [`sort_parallel`] ordered by `compare`, which every thread shares.
+pub fn sort_parallel_by<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(
+    arr: &mut [T],
+    cfg: &SortConfig,
+    compare: &F,
+) {
+    if arr.len() < 2 {
+        return;
+    }
+    // Threads cannot be spawned on WASM without the `atomics` feature
+    let threads = if cfg!(all(target_arch = "wasm32", not(target_feature = "atomics"))) {
+        1
+    } else if cfg.max_threads == 0 {
+        thread::available_parallelism().map_or(1, |n| n.get())
+    } else {
+        cfg.max_threads
+    };
+    #[cfg(feature = "rayon")]
+    if cfg.backend == Backend::WaveSort {
+        sort_rayon(arr, cfg, threads, compare);
+        return;
+    }
+    sort_split(arr, cfg, threads, compare);
+}
+
+/// Sorts `arr` on a rayon pool of at most `threads` threads: the global pool
+/// when `cfg.max_threads` is 0, otherwise one built for this call. A slice
+/// too short for both halves to reach `cfg.parallel_threshold` stays on the
+/// calling thread, as in `sort_split`.
+#[cfg(feature = "rayon")]
+fn sort_rayon<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(
+    arr: &mut [T],
+    cfg: &SortConfig,
+    threads: usize,
+    compare: &F,
+) {
+    if threads < 2 || arr.len() / 2 < cfg.parallel_threshold {
+        wavesort_rust::sort_by_config(arr, cfg, compare);
+        return;
+    }
+    if cfg.max_threads == 0 {
+        wavesort_rust::sort_rayon(arr, cfg, compare);
+        return;
+    }
+    match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+        Ok(pool) => pool.install(|| wavesort_rust::sort_rayon(arr, cfg, compare)),
+        // The pool's threads could not be started: sort on this one
+        Err(_) => wavesort_rust::sort_by_config(arr, cfg, compare),
+    }
+}
+
+/// Sorts `arr` using at most `threads` threads, the calling one included.
+///
+/// Halves are sorted concurrently, with the threads divided between them,
+/// and then merged in place. A slice is only split if both halves are at
+/// least `cfg.parallel_threshold` long, so no thread sorts a shorter piece.
+fn sort_split<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(
+    arr: &mut [T],
+    cfg: &SortConfig,
+    threads: usize,
+    compare: &F,
+) {
+    if threads < 2 || arr.len() / 2 < cfg.parallel_threshold {
+        wavesort_rust::sort_by_config(arr, cfg, compare);
+        return;
+    }
+    let mid = arr.len() / 2;
+    let (lo, hi) = arr.split_at_mut(mid);
+    thread::scope(|s| {
+        s.spawn(|| sort_split(lo, cfg, threads / 2, compare));
+        sort_split(hi, cfg, threads - threads / 2, compare);
+    });
+    let mut compare = compare;
+    wavesort_rust::merge_by(arr, mid, &mut compare);
+}
@@ -0,0 +1,63 @@
+/// Radix digits per key: four passes of eight bits.
+const PASSES: usize = 4;
+
+/// Maps an `i32` to a `u32` with the same ordering by flipping the sign bit.
+#[inline(always)]
+fn key(x: i32) -> u32 {
+    (x as u32) ^ 0x8000_0000
+}
+
+/// LSD radix sort of `i32`s through a scratch buffer of the same length.
+///
+/// All four digit histograms are built in one read of the input, and a pass
+/// whose digit is the same for every element is skipped.
+pub fn sort_i32(arr: &mut [i32]) {
+    sort_i32_with(arr, &mut Vec::new());
+}
+
+/// [`sort_i32`] through a caller-owned scratch buffer, which is grown to
+/// `arr.len()` if needed and keeps its capacity afterwards.
+pub fn sort_i32_with(arr: &mut [i32], scratch: &mut Vec<i32>) {
+    let len = arr.len();
+    if len < 2 {
+        return;
+    }
+    let mut counts = [[0usize; 256]; PASSES];
+    for &x in arr.iter() {
+        let k = key(x);
+        for (pass, count) in counts.iter_mut().enumerate() {
+            count[((k >> (pass * 8)) & 0xff) as usize] += 1;
+        }
+    }
+
+    scratch.clear();
+    scratch.resize(len, 0);
+    let scratch = &mut scratch[..];
+    let mut in_scratch = false;
+    for (pass, count) in counts.iter().enumerate() {
+        if count.contains(&len) {
+            continue;
+        }
+        let mut offsets = [0usize; 256];
+        let mut sum = 0;
+        for (offset, &c) in offsets.iter_mut().zip(count.iter()) {
+            *offset = sum;
+            sum += c;
+        }
+        let (src, dst): (&[i32], &mut [i32]) = if in_scratch {
+            (&*scratch, &mut *arr)
+        } else {
+            (&*arr, &mut *scratch)
+        };
+        let shift = pass * 8;
+        for &x in src {
+            let digit = ((key(x) >> shift) & 0xff) as usize;
+            dst[offsets[digit]] = x;
+            offsets[digit] += 1;
+        }
+        in_scratch = !in_scratch;
+    }
+    if in_scratch {
+        arr.copy_from_slice(scratch);
+    }
+}
@@ -0,0 +1,100 @@
+//! AVX2 sorting networks for the short leaf runs of `sort_i32`.
+//!
+//! Each network layer pairs every lane with a partner via a permute, takes
+//! the lane-wise min and max, and blends the min into the lower lane of each
+//! pair and the max into the upper one.
+
+use core::arch::x86_64::*;
+
+/// Returns the AVX2 leaf sorter if the running CPU supports it.
+pub fn small_sort_i32() -> Option<fn(&mut [i32]) -> bool> {
+    if is_x86_feature_detected!("avx2") {
+        Some(sort_small)
+    } else {
+        None
+    }
+}
+
+/// Sorts runs of 2 to 16 `i32`s, padding them to 8 or 16 lanes with
+/// `i32::MAX`. Returns `false`, leaving `arr` untouched, for other lengths.
+///
+/// Only ever handed out by [`small_sort_i32`] after AVX2 was detected.
+fn sort_small(arr: &mut [i32]) -> bool {
+    let len = arr.len();
+    if !(2..=16).contains(&len) {
+        return false;
+    }
+    let mut buf = [i32::MAX; 16];
+    buf[..len].copy_from_slice(arr);
+    // SAFETY: `small_sort_i32` only returns this function when AVX2 is
+    // available, and `buf` holds 16 lanes.
+    unsafe {
+        if len <= 8 {
+            sort8_buf(&mut buf);
+        } else {
+            sort16_buf(&mut buf);
+        }
+    }
+    arr.copy_from_slice(&buf[..len]);
+    true
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn sort8_buf(buf: &mut [i32; 16]) {
+    let ptr = buf.as_mut_ptr() as *mut __m256i;
+    unsafe {
+        let v = sort8(_mm256_loadu_si256(ptr));
+        _mm256_storeu_si256(ptr, v);
+    }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn sort16_buf(buf: &mut [i32; 16]) {
+    let ptr = buf.as_mut_ptr() as *mut __m256i;
+    unsafe {
+        let a = sort8(_mm256_loadu_si256(ptr));
+        let b = sort8(_mm256_loadu_si256(ptr.add(1)));
+        // Reversing `b` makes `a ++ b` bitonic; one min/max step splits it
+        // into the 8 smallest and the 8 largest, each still bitonic.
+        let b = _mm256_permutevar8x32_epi32(b, _mm256_setr_epi32(7, 6, 5, 4, 3, 2, 1, 0));
+        let lo = bitonic_merge8(_mm256_min_epi32(a, b));
+        let hi = bitonic_merge8(_mm256_max_epi32(a, b));
+        _mm256_storeu_si256(ptr, lo);
+        _mm256_storeu_si256(ptr.add(1), hi);
+    }
+}
+
+/// One network layer: `idx` maps each lane to its partner and `MAX_LANES`
+/// marks the upper lane of each pair.
+#[target_feature(enable = "avx2")]
+fn layer<const MAX_LANES: i32>(v: __m256i, idx: __m256i) -> __m256i {
+    let p = _mm256_permutevar8x32_epi32(v, idx);
+    let mn = _mm256_min_epi32(v, p);
+    let mx = _mm256_max_epi32(v, p);
+    _mm256_blend_epi32::<MAX_LANES>(mn, mx)
+}
+
+/// Batcher's odd-even merge sort for 8 lanes: 19 comparators in 6 layers.
+#[target_feature(enable = "avx2")]
+fn sort8(v: __m256i) -> __m256i {
+    // (0,1) (2,3) (4,5) (6,7)
+    let v = layer::<0b1010_1010>(v, _mm256_setr_epi32(1, 0, 3, 2, 5, 4, 7, 6));
+    // (0,2) (1,3) (4,6) (5,7)
+    let v = layer::<0b1100_1100>(v, _mm256_setr_epi32(2, 3, 0, 1, 6, 7, 4, 5));
+    // (1,2) (5,6)
+    let v = layer::<0b0100_0100>(v, _mm256_setr_epi32(0, 2, 1, 3, 4, 6, 5, 7));
+    // (0,4) (1,5) (2,6) (3,7)
+    let v = layer::<0b1111_0000>(v, _mm256_setr_epi32(4, 5, 6, 7, 0, 1, 2, 3));
+    // (2,4) (3,5)
+    let v = layer::<0b0011_0000>(v, _mm256_setr_epi32(0, 1, 4, 5, 2, 3, 6, 7));
+    // (1,2) (3,4) (5,6)
+    layer::<0b0101_0100>(v, _mm256_setr_epi32(0, 2, 1, 4, 3, 6, 5, 7))
+}
+
+/// Sorts a bitonic 8-lane vector with half-cleaners at distances 4, 2, 1.
+#[target_feature(enable = "avx2")]
+fn bitonic_merge8(v: __m256i) -> __m256i {
+    let v = layer::<0b1111_0000>(v, _mm256_setr_epi32(4, 5, 6, 7, 0, 1, 2, 3));
+    let v = layer::<0b1100_1100>(v, _mm256_setr_epi32(2, 3, 0, 1, 6, 7, 4, 5));
+    layer::<0b1010_1010>(v, _mm256_setr_epi32(1, 0, 3, 2, 5, 4, 7, 6))
+}
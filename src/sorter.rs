@@ -0,0 +1,69 @@
+use crate::{SortConfig, counting, radix};
+
+/// Sorts many `i32` slices, keeping scratch buffers between calls.
+///
+/// [`sort_i32_with_config`](crate::sort_i32_with_config) allocates a
+/// scratch buffer on every call that takes the radix or counting path. A
+/// `WaveSorter` grows its buffers on demand and retains them, so sorting
+/// many slices of similar length allocates only once. Slices sorted in
+/// place by WaveSort leave the buffers untouched.
+///
+/// ```
+/// use wsort::{SortConfig, WaveSorter};
+///
+/// let mut sorter = WaveSorter::with_config(SortConfig {
+///     radix_threshold: Some(1024),
+///     ..Default::default()
+/// });
+/// for seed in 0..4 {
+///     let mut v: Vec<i32> = (0..5000).map(|i| (i * 7919 + seed) % 5003).collect();
+///     sorter.sort(&mut v);
+///     assert!(wsort::is_sorted(&v));
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WaveSorter {
+    cfg: SortConfig,
+    scratch: Vec<i32>,
+    counts: Vec<usize>,
+}
+
+impl WaveSorter {
+    /// A sorter with the default [`SortConfig`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A sorter with custom tuning, honored as by
+    /// [`sort_i32_with_config`](crate::sort_i32_with_config).
+    pub fn with_config(cfg: SortConfig) -> Self {
+        WaveSorter {
+            cfg,
+            scratch: Vec::new(),
+            counts: Vec::new(),
+        }
+    }
+
+    /// The tuning this sorter uses.
+    pub fn config(&self) -> &SortConfig {
+        &self.cfg
+    }
+
+    /// Sorts `arr` in place, with the same result as
+    /// [`sort_i32_with_config`](crate::sort_i32_with_config).
+    pub fn sort(&mut self, arr: &mut [i32]) {
+        if self.cfg.radix_threshold.is_some_and(|t| arr.len() > t) {
+            radix::sort_i32_with(arr, &mut self.scratch);
+            return;
+        }
+        if counting::detect_and_sort_with(arr, &mut self.counts) {
+            return;
+        }
+        crate::wave_sort_i32(arr, &self.cfg);
+    }
+
+    /// Elements the radix scratch buffer can hold without reallocating.
+    pub fn scratch_capacity(&self) -> usize {
+        self.scratch.capacity()
+    }
+}
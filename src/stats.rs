@@ -0,0 +1,37 @@
+/// Operation counts collected by [`sort_instrumented`](crate::sort_instrumented).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortStats {
+    /// Calls to the comparison function.
+    pub comparisons: u64,
+    /// Element swaps made by `partition` and the heapsort fallback.
+    pub swaps: u64,
+    /// Deepest nesting of the wave recursion.
+    pub max_depth: u32,
+}
+
+/// Hooks the sort calls as it works. The `()` implementation does nothing,
+/// so the uninstrumented sort compiles to the same code as before.
+pub(crate) trait Counter {
+    fn compared(&mut self) {}
+    fn swapped(&mut self) {}
+    fn reached_depth(&mut self, _depth: u32) {}
+}
+
+impl Counter for () {}
+
+impl Counter for SortStats {
+    #[inline]
+    fn compared(&mut self) {
+        self.comparisons += 1;
+    }
+
+    #[inline]
+    fn swapped(&mut self) {
+        self.swaps += 1;
+    }
+
+    #[inline]
+    fn reached_depth(&mut self, depth: u32) {
+        self.max_depth = self.max_depth.max(depth);
+    }
+}
@@ -1,15 +1,30 @@
 ; ==============================================================================
 ; Wave Sort - Highly Optimized AMD64 Assembly Implementation
-; Target: AMD64 (x86_64) with AVX2 support
+; Target: AMD64 (x86_64); wave_sort_avx2 requires AVX2, wave_sort does not
 ; Enhancements:
-;   - AVX2 Vectorized Block Swaps (block_swap_sr) Unrolled x2
+;   - AVX2 Vectorized Block Swaps (block_swap_sr) Unrolled x2, or x4 when
+;     assembled with -DWSORT_MODERN (see WSORT_ARCH in build.rs)
 ;   - AVX2 Partitioning Unrolled x2 with Correct Mask Logic
 ;   - Cache Prefetching (partition)
 ;   - 16-byte Loop Alignment
+;   - Scalar template (WAVE_SORT_SCALAR) for CPUs without AVX2 and for
+;     other element types (int64_t, uint32_t, int16_t), with optional SSE2
+;     scans and block swaps (wave_sort_sse2, the baseline every x86-64 has)
 ; ==============================================================================
 
+; Mach-O C symbols carry a leading underscore
+%ifidn __OUTPUT_FORMAT__, macho64
+%define wave_sort       _wave_sort
+%define wave_sort_avx2  _wave_sort_avx2
+%define wave_sort_sse2  _wave_sort_sse2
+%define wave_sort_i64   _wave_sort_i64
+%define wave_sort_u32   _wave_sort_u32
+%define wave_sort_cmp   _wave_sort_cmp
+%define wave_sort_i16   _wave_sort_i16
+%endif
+
 section .text
-global wave_sort
+global wave_sort_avx2
 
 ; ==============================================================================
 ; Helper Macros and Constants
@@ -17,6 +32,26 @@ global wave_sort
 
 %define SIZEOF_INT 4
 
+; Exported entry points are written against the System V calling convention.
+; On Windows x64, arguments arrive in RCX/RDX and RDI/RSI are callee-saved, so
+; each entry point starts with a shim that moves the arguments into place and
+; preserves RDI/RSI around the System V body.
+%macro SYSV_ENTRY 0
+%ifidn __OUTPUT_FORMAT__, win64
+    push    rdi
+    push    rsi
+    mov     rdi, rcx
+    mov     rsi, rdx
+    sub     rsp, 8
+    call    %%body
+    add     rsp, 8
+    pop     rsi
+    pop     rdi
+    ret
+%%body:
+%endif
+%endmacro
+
 ; ==============================================================================
 ; Function: swap
 ; RDI = int32_t *a
@@ -130,6 +165,41 @@ block_swap_sr:
     mov     r8d, [rdi + rsi*4] ; r8d = tmp
     ; j = r  (RDX)
 
+%ifdef WSORT_MODERN
+    ; Modern variant: 32 elements/iter while they last, trading the loop
+    ; overhead for code size. Entering this loop means ll = j - i >= 33, so
+    ; no load reads an element stored earlier in the same iteration. Only
+    ; YMM0-YMM3 are used, as XMM6+ are callee-saved on Windows x64.
+    align 16
+.sr_avx_x4_loop:
+    lea     rax, [rdx + 32]
+    cmp     rax, rcx
+    ja      .sr_avx_x4_done
+
+    vmovdqu ymm0, [rdi + rdx*4]
+    vmovdqu ymm1, [rdi + rsi*4 + 4]
+    vmovdqu ymm2, [rdi + rdx*4 + 32]
+    vmovdqu ymm3, [rdi + rsi*4 + 36]
+    vmovdqu [rdi + rsi*4], ymm0
+    vmovdqu [rdi + rdx*4], ymm1
+    vmovdqu [rdi + rsi*4 + 32], ymm2
+    vmovdqu [rdi + rdx*4 + 32], ymm3
+
+    vmovdqu ymm0, [rdi + rdx*4 + 64]
+    vmovdqu ymm1, [rdi + rsi*4 + 68]
+    vmovdqu ymm2, [rdi + rdx*4 + 96]
+    vmovdqu ymm3, [rdi + rsi*4 + 100]
+    vmovdqu [rdi + rsi*4 + 64], ymm0
+    vmovdqu [rdi + rdx*4 + 64], ymm1
+    vmovdqu [rdi + rsi*4 + 96], ymm2
+    vmovdqu [rdi + rdx*4 + 96], ymm3
+
+    add     rsi, 32
+    add     rdx, 32
+    jmp     .sr_avx_x4_loop
+
+.sr_avx_x4_done:
+%endif
     ; Check if we can use AVX2 Unrolled (need at least 16 elements)
     ; condition: j + 16 <= p
     lea     rax, [rdx + 16]
@@ -739,11 +809,13 @@ upwave:
     ret
 
 ; ==============================================================================
-; Function: wave_sort
-; Entry Point
+; Function: wave_sort_avx2
+; Entry Point (AVX2)
 ; ==============================================================================
 align 16
-wave_sort:
+wave_sort_avx2:
+    SYSV_ENTRY
+    ; Return before touching arr: empty slices pass a dangling pointer
     test    rdi, rdi
     jz      .ws_done
     cmp     rsi, 2
@@ -755,4 +827,725 @@ wave_sort:
     call    upwave
 
 .ws_done:
-    ret
\ No newline at end of file
+    ret
+; ==============================================================================
+; Scalar Wave Sort Template
+; Instantiates a complete scalar (non-vectorized) Wave Sort under the symbol
+; %1, with private helpers prefixed by %1. The element type is selected by the
+; single-line macros below, which must be defined before each instantiation:
+;   EL_SIZE              - element size in bytes (scale factor)
+;   EL_RAX .. EL_R11     - RAX, R8, R9, R10, R11 at the element width
+;   EL_JGE / EL_JLE      - "greater or equal" / "less or equal" jumps
+;                          (signed jge/jle or unsigned jae/jbe)
+;   EL_SSE2 (optional)   - if defined, vectorize the partition scans and
+;                          block swaps with SSE2; signed 32-bit elements only
+;   EL_DESC (optional)   - if defined, sort descending: EL_JGE / EL_JLE must
+;                          be passed swapped (jle / jge), the SSE2 compares
+;                          swap their operands to match, and no entry point
+;                          is emitted (wave_sort_cmp calls %1_upwave)
+; ==============================================================================
+
+%macro WAVE_SORT_SCALAR 1
+
+; ------------------------------------------------------------------------------
+; Function: %{1}_block_swap_sl
+; Params: RDI=arr, RSI=m, RDX=p, RCX=ll
+; ------------------------------------------------------------------------------
+align 16
+%{1}_block_swap_sl:
+    push    r12
+    push    r13
+    push    rbx
+
+    mov     EL_R8, [rdi + rsi*EL_SIZE]  ; tmp = arr[m]
+    mov     r9, rsi                     ; init = m
+    mov     r10, rsi                    ; j = m
+
+    ; nm = p - ll + 1
+    mov     r11, rdx
+    sub     r11, rcx
+    inc     r11
+
+    ; total_len = p - m + 1
+    mov     r13, rdx
+    sub     r13, rsi
+    inc     r13
+
+    xor     r12, r12                    ; count = 0
+
+    align 16
+.loop_body:
+    cmp     r12, r13
+    jge     .exit_sl
+
+    cmp     r10, r11                    ; if (j >= nm)
+    jb      .sl_else
+
+    ; k = j - nm + m
+    mov     rbx, r10
+    sub     rbx, r11
+    add     rbx, rsi
+
+    cmp     rbx, r9                     ; if (k == init)
+    jne     .sl_cycle_cont
+
+    ; init++; arr[j] = tmp; j = init; tmp = arr[j];
+    inc     r9
+    mov     [rdi + r10*EL_SIZE], EL_R8
+    mov     r10, r9
+    mov     EL_R8, [rdi + r10*EL_SIZE]
+    jmp     .sl_next
+
+.sl_cycle_cont:
+    ; arr[j] = arr[k]; j = k;
+    mov     EL_RAX, [rdi + rbx*EL_SIZE]
+    mov     [rdi + r10*EL_SIZE], EL_RAX
+    mov     r10, rbx
+    jmp     .sl_next
+
+.sl_else:
+    ; k = j + ll; arr[j] = arr[k]; j = k;
+    mov     rbx, r10
+    add     rbx, rcx
+    mov     EL_RAX, [rdi + rbx*EL_SIZE]
+    mov     [rdi + r10*EL_SIZE], EL_RAX
+    mov     r10, rbx
+
+.sl_next:
+    inc     r12
+    jmp     .loop_body
+
+.exit_sl:
+    pop     rbx
+    pop     r13
+    pop     r12
+    ret
+
+; ------------------------------------------------------------------------------
+; Function: %{1}_block_swap_sr
+; Params: RDI=arr, RSI=m, RDX=r, RCX=p
+; ------------------------------------------------------------------------------
+align 16
+%{1}_block_swap_sr:
+    mov     EL_R8, [rdi + rsi*EL_SIZE]  ; tmp = arr[i]
+
+%ifdef EL_SSE2
+    ; 8 elements/iter while j + 8 <= p. As in the AVX2 block_swap_sr,
+    ; ll = j - i > 8 here, so the loads never see this iteration's stores.
+    align 16
+.sr_sse2_loop:
+    lea     rax, [rdx + 8]
+    cmp     rax, rcx
+    ja      .sr_scalar_loop
+
+    movdqu  xmm0, [rdi + rdx*4]
+    movdqu  xmm1, [rdi + rsi*4 + 4]
+    movdqu  xmm2, [rdi + rdx*4 + 16]
+    movdqu  xmm3, [rdi + rsi*4 + 20]
+    movdqu  [rdi + rsi*4], xmm0
+    movdqu  [rdi + rdx*4], xmm1
+    movdqu  [rdi + rsi*4 + 16], xmm2
+    movdqu  [rdi + rdx*4 + 16], xmm3
+
+    add     rsi, 8
+    add     rdx, 8
+    jmp     .sr_sse2_loop
+%endif
+
+    align 16
+.sr_scalar_loop:
+    cmp     rdx, rcx                    ; while (j < p)
+    jge     .sr_done
+
+    ; arr[i] = arr[j]; i++; arr[j] = arr[i]; j++;
+    mov     EL_R9, [rdi + rdx*EL_SIZE]
+    mov     [rdi + rsi*EL_SIZE], EL_R9
+    inc     rsi
+    mov     EL_R9, [rdi + rsi*EL_SIZE]
+    mov     [rdi + rdx*EL_SIZE], EL_R9
+    inc     rdx
+    jmp     .sr_scalar_loop
+
+.sr_done:
+    ; arr[i] = arr[j]; arr[j] = tmp;
+    mov     EL_R9, [rdi + rdx*EL_SIZE]
+    mov     [rdi + rsi*EL_SIZE], EL_R9
+    mov     [rdi + rdx*EL_SIZE], EL_R8
+    ret
+
+; ------------------------------------------------------------------------------
+; Function: %{1}_block_swap
+; Params: RDI=arr, RSI=m, RDX=r, RCX=p
+; ------------------------------------------------------------------------------
+align 16
+%{1}_block_swap:
+    mov     r8, rdx
+    sub     r8, rsi                     ; ll = r - m
+    jz      .bs_ret
+
+    mov     r9, rcx
+    sub     r9, rdx
+    inc     r9                          ; lr = p - r + 1
+
+    cmp     r9, 1
+    jne     .bs_check_size
+
+    ; if (lr == 1) swap(&arr[m], &arr[p]);
+    mov     EL_R8, [rdi + rsi*EL_SIZE]
+    mov     EL_R9, [rdi + rcx*EL_SIZE]
+    mov     [rdi + rsi*EL_SIZE], EL_R9
+    mov     [rdi + rcx*EL_SIZE], EL_R8
+    ret
+
+.bs_check_size:
+    cmp     r9, r8
+    ja      .bs_call_sl
+
+    call    %{1}_block_swap_sr
+    ret
+
+.bs_call_sl:
+    mov     rdx, rcx                    ; p
+    mov     rcx, r8                     ; ll
+    call    %{1}_block_swap_sl
+.bs_ret:
+    ret
+
+; ------------------------------------------------------------------------------
+; Function: %{1}_partition
+; Params: RDI=arr, RSI=l, RDX=r, RCX=p_idx
+; Returns: i (RAX)
+; ------------------------------------------------------------------------------
+align 16
+%{1}_partition:
+    mov     EL_R10, [rdi + rcx*EL_SIZE] ; pivot_val = arr[p_idx]
+    mov     rax, rsi
+    dec     rax                         ; i = l - 1
+    mov     r8, rdx                     ; j = r
+%ifdef EL_SSE2
+    movd    xmm0, r10d
+    pshufd  xmm0, xmm0, 0               ; pivot in all four lanes
+%endif
+
+    align 16
+.scan_i:
+    inc     rax                         ; i++
+    cmp     rax, r8
+    je      .part_done
+%ifdef EL_SSE2
+    ; Two vectors (8 elements) at a time while they lie below j. A lane
+    ; stops the scan when NOT (pivot > val), i.e. its 4 mask bits are 0
+    ; (NOT (val > pivot) descending).
+    mov     r9, r8
+    sub     r9, rax
+    cmp     r9, 8
+    jl      .scan_i_scalar
+
+    movdqu  xmm1, [rdi + rax*4]         ; i ... i+3
+    movdqu  xmm2, [rdi + rax*4 + 16]    ; i+4 ... i+7
+%ifdef EL_DESC
+    movdqa  xmm3, xmm1
+    movdqa  xmm4, xmm2
+    pcmpgtd xmm3, xmm0
+    pcmpgtd xmm4, xmm0
+%else
+    movdqa  xmm3, xmm0
+    movdqa  xmm4, xmm0
+    pcmpgtd xmm3, xmm1
+    pcmpgtd xmm4, xmm2
+%endif
+    pmovmskb r9d, xmm3
+    pmovmskb r11d, xmm4
+    mov     ecx, r9d
+    and     ecx, r11d
+    cmp     ecx, 0xFFFF
+    jne     .found_i
+
+    add     rax, 7                      ; +8 total
+    jmp     .scan_i
+
+.found_i:
+    xor     r9d, 0xFFFF                 ; set bits mark val >= pivot (<=)
+    jnz     .found_i_vec1
+    xor     r11d, 0xFFFF
+    bsf     r11d, r11d
+    shr     r11d, 2
+    lea     rax, [rax + r11 + 4]
+    jmp     .scan_j
+
+.found_i_vec1:
+    bsf     r9d, r9d
+    shr     r9d, 2
+    add     rax, r9
+    jmp     .scan_j
+
+.scan_i_scalar:
+%endif
+    mov     EL_R11, [rdi + rax*EL_SIZE]
+    cmp     EL_R11, EL_R10
+    EL_JGE  .scan_j                     ; stop on arr[i] >= pivot
+    jmp     .scan_i
+
+    align 16
+.scan_j:
+    dec     r8                          ; j--
+    cmp     r8, rax
+    je      .part_done
+%ifdef EL_SSE2
+    ; Mirror of the i scan over [j-7 .. j], highest index first. A lane
+    ; stops the scan when NOT (val > pivot) (NOT (pivot > val) descending).
+    mov     r9, r8
+    sub     r9, rax
+    cmp     r9, 8
+    jl      .scan_j_scalar
+
+    movdqu  xmm1, [rdi + r8*4 - 12]     ; j-3 ... j
+    movdqu  xmm2, [rdi + r8*4 - 28]     ; j-7 ... j-4
+%ifdef EL_DESC
+    movdqa  xmm3, xmm0
+    movdqa  xmm4, xmm0
+    pcmpgtd xmm3, xmm1
+    pcmpgtd xmm4, xmm2
+%else
+    movdqa  xmm3, xmm1
+    movdqa  xmm4, xmm2
+    pcmpgtd xmm3, xmm0
+    pcmpgtd xmm4, xmm0
+%endif
+    pmovmskb r11d, xmm3
+    pmovmskb r9d, xmm4
+    mov     ecx, r11d
+    and     ecx, r9d
+    cmp     ecx, 0xFFFF
+    jne     .found_j
+
+    sub     r8, 7                       ; -8 total
+    jmp     .scan_j
+
+.found_j:
+    xor     r11d, 0xFFFF                ; set bits mark val <= pivot (>=)
+    jnz     .found_j_vec1
+    xor     r9d, 0xFFFF
+    bsr     r9d, r9d
+    shr     r9d, 2
+    sub     r8, 7
+    add     r8, r9
+    jmp     .found_j_load
+
+.found_j_vec1:
+    bsr     r11d, r11d
+    shr     r11d, 2
+    sub     r8, 3
+    add     r8, r11
+
+.found_j_load:
+    mov     r11d, [rdi + r8*4]
+    jmp     .swap_ij
+
+.scan_j_scalar:
+%endif
+    mov     EL_R11, [rdi + r8*EL_SIZE]
+    cmp     EL_R11, EL_R10
+    EL_JLE  .swap_ij                    ; stop on arr[j] <= pivot
+    jmp     .scan_j
+
+.swap_ij:
+    mov     EL_R9, [rdi + rax*EL_SIZE]
+    mov     [rdi + rax*EL_SIZE], EL_R11
+    mov     [rdi + r8*EL_SIZE], EL_R9
+    jmp     .scan_i
+
+.part_done:
+    ret
+
+; ------------------------------------------------------------------------------
+; Function: %{1}_downwave (Recursion)
+; ------------------------------------------------------------------------------
+align 16
+%{1}_downwave:
+    push    rbp
+    push    rbx
+    push    r12
+    push    r13
+    push    r14
+    push    r15
+    sub     rsp, 8
+
+    cmp     rdx, rsi
+    je      .dw_return
+
+    mov     rbx, rdi ; arr
+    mov     r12, rsi ; start
+    mov     r13, rdx ; sorted_start
+    mov     r14, rcx ; end
+
+    ; p = sorted_start + (end - sorted_start) / 2
+    mov     rax, r14
+    sub     rax, r13
+    shr     rax, 1
+    add     rax, r13
+    mov     r15, rax ; p
+
+    mov     rdi, rbx
+    mov     rsi, r12
+    mov     rdx, r13
+    mov     rcx, r15
+    call    %{1}_partition
+
+    ; m is in RAX
+    cmp     rax, r13
+    jne     .dw_not_sorted_start
+
+    ; m == sorted_start
+    cmp     r15, r13
+    jne     .dw_check_p_gt_0
+
+    test    r13, r13
+    jz      .dw_return
+    mov     rdi, rbx
+    mov     rsi, r12
+    mov     rdx, r13
+    dec     rdx
+    call    %{1}_upwave
+    jmp     .dw_return
+
+.dw_check_p_gt_0:
+    test    r15, r15
+    jz      .dw_return
+    mov     rdi, rbx
+    mov     rsi, r12
+    mov     rdx, r13
+    mov     rcx, r15
+    dec     rcx
+    call    %{1}_downwave
+    jmp     .dw_return
+
+.dw_not_sorted_start:
+    mov     rbp, rax ; m
+
+    mov     rdi, rbx
+    mov     rsi, rbp
+    mov     rdx, r13
+    mov     rcx, r15
+    call    %{1}_block_swap
+
+    cmp     rbp, r12
+    jne     .dw_check_p_sorted
+
+    ; m == start
+    cmp     r15, r13
+    jne     .dw_m_start_next
+
+    mov     rdi, rbx
+    mov     rsi, rbp
+    inc     rsi
+    mov     rdx, r14
+    call    %{1}_upwave
+    jmp     .dw_return
+
+.dw_m_start_next:
+    lea     rax, [r15 + 1]
+    mov     rsi, rbp
+    add     rsi, rax
+    sub     rsi, r13
+
+    mov     rdi, rbx
+    mov     rdx, rax
+    mov     rcx, r14
+    call    %{1}_downwave
+    jmp     .dw_return
+
+.dw_check_p_sorted:
+    cmp     r15, r13
+    jne     .dw_final_split
+
+    test    rbp, rbp
+    jz      .dw_do_second_up
+    mov     rdi, rbx
+    mov     rsi, r12
+    mov     rdx, rbp
+    dec     rdx
+    call    %{1}_upwave
+
+.dw_do_second_up:
+    mov     rdi, rbx
+    mov     rsi, rbp
+    inc     rsi
+    mov     rdx, r14
+    call    %{1}_upwave
+    jmp     .dw_return
+
+.dw_final_split:
+    mov     rax, r15
+    sub     rax, r13
+    mov     r8, rbp
+    add     r8, rax ; split_point
+
+    test    r8, r8
+    jz      .dw_second_rec
+
+    push    r8
+    mov     rdi, rbx
+    mov     rsi, r12
+    mov     rdx, rbp
+    mov     rcx, r8
+    dec     rcx
+    call    %{1}_downwave
+    pop     r8
+
+.dw_second_rec:
+    mov     rdi, rbx
+    mov     rsi, r8
+    inc     rsi
+    mov     rdx, r15
+    inc     rdx
+    mov     rcx, r14
+    call    %{1}_downwave
+
+.dw_return:
+    add     rsp, 8
+    pop     r15
+    pop     r14
+    pop     r13
+    pop     r12
+    pop     rbx
+    pop     rbp
+    ret
+
+; ------------------------------------------------------------------------------
+; Function: %{1}_upwave (Recursion)
+; ------------------------------------------------------------------------------
+align 16
+%{1}_upwave:
+    push    rbp
+    push    rbx
+    push    r12
+    push    r13
+    push    r14
+
+    cmp     rsi, rdx
+    je      .uw_exit
+
+    mov     rbx, rdi
+    mov     r12, rsi
+    mov     r13, rdx
+
+    test    r13, r13
+    jz      .uw_exit
+
+    mov     r14, r13
+    mov     rbp, 1
+
+    push    r15
+    mov     r15, r13
+    dec     r15
+
+    align 16
+.uw_loop:
+    mov     rdi, rbx
+    mov     rsi, r15
+    mov     rdx, r14
+    mov     rcx, r13
+    call    %{1}_downwave
+
+    mov     r14, r15
+    mov     rbp, r13
+    sub     rbp, r14
+    inc     rbp
+
+    mov     rax, r13
+    sub     rax, r12
+    inc     rax
+
+    mov     rcx, rbp
+    shl     rcx, 2
+    cmp     rax, rcx
+    jl      .uw_break
+
+    mov     rcx, rbp
+    shl     rcx, 1
+    inc     rcx
+
+    cmp     r13, rcx
+    jb      .uw_set_start
+
+    mov     rax, r13
+    sub     rax, rcx
+    cmp     rax, r12
+    jb      .uw_set_start
+
+    mov     r15, rax
+    jmp     .uw_check_lb
+
+.uw_set_start:
+    mov     r15, r12
+
+.uw_check_lb:
+    cmp     r15, r12
+    jae     .uw_check_ss
+    mov     r15, r12
+
+.uw_check_ss:
+    cmp     r14, r12
+    je      .uw_break
+
+    jmp     .uw_loop
+
+.uw_break:
+    mov     rdi, rbx
+    mov     rsi, r12
+    mov     rdx, r14
+    mov     rcx, r13
+    call    %{1}_downwave
+
+    pop     r15
+.uw_exit:
+    pop     r14
+    pop     r13
+    pop     r12
+    pop     rbx
+    pop     rbp
+    ret
+
+%ifndef EL_DESC
+; ------------------------------------------------------------------------------
+; Function: %1
+; Entry Point
+; ------------------------------------------------------------------------------
+global %1
+align 16
+%1:
+    SYSV_ENTRY
+    ; Return before touching arr: empty slices pass a dangling pointer
+    test    rdi, rdi
+    jz      .ws_done
+    cmp     rsi, 2
+    jb      .ws_done
+
+    dec     rsi      ; end = n - 1
+    mov     rdx, rsi
+    xor     rsi, rsi ; start = 0
+    call    %{1}_upwave
+
+.ws_done:
+    ret
+%endif
+
+%endmacro
+
+; ==============================================================================
+; Function: wave_sort
+; Signature: void wave_sort(int32_t *arr, size_t len);
+; Scalar version, with no vector instructions at all.
+; ==============================================================================
+%define EL_SIZE 4
+%define EL_RAX  eax
+%define EL_R8   r8d
+%define EL_R9   r9d
+%define EL_R10  r10d
+%define EL_R11  r11d
+%define EL_JGE  jge
+%define EL_JLE  jle
+WAVE_SORT_SCALAR wave_sort
+
+; ==============================================================================
+; Function: wave_sort_sse2
+; Signature: void wave_sort_sse2(int32_t *arr, size_t len);
+; wave_sort with SSE2 partition scans and block swaps. SSE2 is part of
+; x86-64, so this runs on every CPU that lacks AVX2.
+; ==============================================================================
+%define EL_SSE2
+WAVE_SORT_SCALAR wave_sort_sse2
+%undef EL_SSE2
+
+; ==============================================================================
+; Function: wave_sort_i64
+; Signature: void wave_sort_i64(int64_t *arr, size_t len);
+; ==============================================================================
+%define EL_SIZE 8
+%define EL_RAX  rax
+%define EL_R8   r8
+%define EL_R9   r9
+%define EL_R10  r10
+%define EL_R11  r11
+%define EL_JGE  jge
+%define EL_JLE  jle
+WAVE_SORT_SCALAR wave_sort_i64
+
+; ==============================================================================
+; Function: wave_sort_u32
+; Signature: void wave_sort_u32(uint32_t *arr, size_t len);
+; Same as wave_sort with unsigned compares.
+; ==============================================================================
+%define EL_SIZE 4
+%define EL_RAX  eax
+%define EL_R8   r8d
+%define EL_R9   r9d
+%define EL_R10  r10d
+%define EL_R11  r11d
+%define EL_JGE  jae
+%define EL_JLE  jbe
+WAVE_SORT_SCALAR wave_sort_u32
+
+; ==============================================================================
+; Descending body of wave_sort_cmp: wave_sort_sse2 with the partition compares
+; swapped. It has no entry point of its own.
+; ==============================================================================
+%define EL_SIZE 4
+%define EL_RAX  eax
+%define EL_R8   r8d
+%define EL_R9   r9d
+%define EL_R10  r10d
+%define EL_R11  r11d
+%define EL_JGE  jle
+%define EL_JLE  jge
+%define EL_SSE2
+%define EL_DESC
+WAVE_SORT_SCALAR wave_sort_cmp_desc
+%undef EL_DESC
+%undef EL_SSE2
+
+; ==============================================================================
+; Function: wave_sort_cmp
+; Signature: void wave_sort_cmp(int32_t *arr, size_t len, int descending);
+; wave_sort_sse2 in either direction: descending runs wave_sort_cmp_desc.
+; SSE2 is the best tier every x86-64 has; the Rust wrapper sends ascending
+; sorts through the detected tier instead, AVX2 included.
+; ==============================================================================
+global wave_sort_cmp
+align 16
+wave_sort_cmp:
+    SYSV_ENTRY
+%ifidn __OUTPUT_FORMAT__, win64
+    mov     rdx, r8             ; third argument
+%endif
+    ; Return before touching arr: empty slices pass a dangling pointer
+    test    rdi, rdi
+    jz      .cmp_done
+    cmp     rsi, 2
+    jb      .cmp_done
+
+    mov     eax, edx            ; descending
+    lea     rdx, [rsi - 1]      ; end = n - 1
+    xor     esi, esi            ; start = 0
+    test    eax, eax
+    jnz     wave_sort_cmp_desc_upwave
+    jmp     wave_sort_sse2_upwave
+
+.cmp_done:
+    ret
+
+; ==============================================================================
+; Function: wave_sort_i16
+; Signature: void wave_sort_i16(int16_t *arr, size_t len);
+; Same as wave_sort with 16-bit loads, stores and signed compares.
+; ==============================================================================
+%define EL_SIZE 2
+%define EL_RAX  ax
+%define EL_R8   r8w
+%define EL_R9   r9w
+%define EL_R10  r10w
+%define EL_R11  r11w
+%define EL_JGE  jge
+%define EL_JLE  jle
+WAVE_SORT_SCALAR wave_sort_i16
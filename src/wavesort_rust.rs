@@ -0,0 +1,1383 @@
+use core::cmp::Ordering;
+use core::hint;
+use core::mem::ManuallyDrop;
+use core::ptr;
+use core::sync::atomic::{self, AtomicBool};
+
+use crate::stats::Counter;
+use crate::{Backend, Partition, SortConfig};
+
+/// Prints one line of the wave recursion to stderr with the `trace` feature
+/// and expands to nothing without it.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "trace")]
+        eprintln!($($arg)*);
+    };
+}
+
+/// Smallest insertion threshold actually used. Lower settings are raised to
+/// this so every wave bottoms out in a leaf sort of at least two elements.
+const MIN_INSERTION_THRESHOLD: usize = 2;
+
+/// Nesting budget for `downwave` before falling back to heapsort.
+///
+/// Every `upwave` expansion starts a fresh logarithmic `downwave` chain, so
+/// presorted inputs legitimately nest on the order of `log2(n)^2` deep. The
+/// budget is `2 * log2(n)^2` so only pathological inputs reach the fallback.
+fn depth_limit(n: usize) -> u32 {
+    let log = usize::BITS - n.leading_zeros();
+    2 * log * log
+}
+
+pub fn sort<T: Ord>(arr: &mut [T]) {
+    sort_by(arr, T::cmp);
+}
+
+// The wave recursion only ever asks `compare` which side of the pivot an
+// element falls on, so flipping the comparator yields a descending sort
+// without a reversal pass.
+pub fn sort_desc<T: Ord>(arr: &mut [T]) {
+    sort_by(arr, |a, b| b.cmp(a));
+}
+
+pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], compare: F) {
+    sort_by_config(arr, &SortConfig::default(), compare);
+}
+
+pub fn sort_by_config<T, F: FnMut(&T, &T) -> Ordering>(
+    arr: &mut [T],
+    cfg: &SortConfig,
+    mut compare: F,
+) {
+    if cfg.backend == Backend::MergeSort {
+        #[cfg(feature = "std")]
+        crate::merge::sort_by(arr, cfg.insertion_threshold, &mut compare);
+        #[cfg(not(feature = "std"))]
+        sort_stable_by(arr, cfg.insertion_threshold, &mut compare);
+        return;
+    }
+    sort_counted(arr, cfg, compare, &mut (), None);
+}
+
+/// Leaf sorter tried before the insertion sorts; returns `false` to decline.
+pub(crate) type SmallSort<T> = fn(&mut [T]) -> bool;
+
+/// `sort_by_config` reporting its work to `counter`, with an optional
+/// `small_sort` for the leaves. `small_sort` must order elements exactly as
+/// `compare` does.
+pub(crate) fn sort_counted<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    cfg: &SortConfig,
+    compare: F,
+    counter: &mut C,
+    small_sort: Option<SmallSort<T>>,
+) {
+    sort_driven(arr, cfg, compare, counter, small_sort, None, drive);
+}
+
+/// `sort_by` that stops between waves once `cancel` is raised, returning
+/// `false` if it did. Every wave moves elements only by swaps and
+/// rotations, so a stopped sort leaves a permutation of the input.
+pub fn sort_cancellable<T, F: FnMut(&T, &T) -> Ordering>(
+    arr: &mut [T],
+    compare: F,
+    cancel: &AtomicBool,
+) -> bool {
+    sort_driven(
+        arr,
+        &SortConfig::default(),
+        compare,
+        &mut (),
+        None,
+        Some(cancel),
+        drive,
+    )
+}
+
+/// `sort_by_config` running the wave recursion on the native stack. Same
+/// output as `sort_by_config`, which uses a work stack when `std` is
+/// available.
+#[cfg(feature = "std")]
+pub fn sort_recursive<T: Ord>(arr: &mut [T], cfg: &SortConfig) {
+    sort_driven(arr, cfg, T::cmp, &mut (), None, None, drive_recursive);
+}
+
+/// `sort_by_config` with the two sides of large downwave splits sorted in
+/// parallel on the current rayon pool (see `drive_rayon`).
+#[cfg(feature = "rayon")]
+pub(crate) fn sort_rayon<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(
+    arr: &mut [T],
+    cfg: &SortConfig,
+    compare: &F,
+) {
+    sort_driven(arr, cfg, compare, &mut (), None, None, drive_rayon);
+}
+
+/// Runs one wave (and everything it leads to) to completion.
+type Driver<T, F, C> = fn(&mut [T], Wave, &mut Ctx<'_, T, F, C>);
+
+/// Sorts `arr`, returning `false` if `cancel` stopped it part-way.
+fn sort_driven<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    cfg: &SortConfig,
+    mut compare: F,
+    counter: &mut C,
+    small_sort: Option<SmallSort<T>>,
+    cancel: Option<&AtomicBool>,
+    driver: Driver<T, F, C>,
+) -> bool {
+    let n = arr.len();
+    if n < 2 {
+        return true;
+    }
+    // Cheap full-array checks: both scans stop at the first out-of-order pair.
+    if arr.windows(2).all(|w| {
+        counter.compared();
+        compare(&w[0], &w[1]) != Ordering::Greater
+    }) {
+        return true;
+    }
+    if arr.windows(2).all(|w| {
+        counter.compared();
+        compare(&w[0], &w[1]) != Ordering::Less
+    }) {
+        arr.reverse();
+        return true;
+    }
+    let threshold = cfg.insertion_threshold.max(MIN_INSERTION_THRESHOLD);
+    let limit = depth_limit(n);
+    let three_way = match cfg.partition {
+        Partition::Auto => has_duplicates(arr, &mut compare, counter),
+        Partition::TwoWay => false,
+        Partition::ThreeWay => true,
+    };
+    let mut ctx = Ctx {
+        compare: &mut compare,
+        counter,
+        small_sort,
+        threshold,
+        limit,
+        cancel,
+        cancelled: false,
+        three_way,
+        #[cfg(feature = "rayon")]
+        parallel_threshold: cfg.parallel_threshold,
+    };
+    if n <= threshold {
+        sort_leaf(arr, &mut ctx, insertion_sort);
+        return true;
+    }
+    driver(
+        arr,
+        Wave::Up {
+            start: 0,
+            end: n - 1,
+            depth: limit,
+        },
+        &mut ctx,
+    );
+    !ctx.cancelled
+}
+
+/// State shared by every level of the wave recursion.
+struct Ctx<'a, T, F, C> {
+    compare: &'a mut F,
+    counter: &'a mut C,
+    small_sort: Option<SmallSort<T>>,
+    threshold: usize,
+    /// Initial depth budget, so `limit - depth` is the current nesting.
+    limit: u32,
+    /// Raised from outside to stop the sort before its next wave.
+    cancel: Option<&'a AtomicBool>,
+    /// Set once a raised `cancel` has been seen; every later step is skipped.
+    cancelled: bool,
+    /// Whether `downwave` looks for repeated pivots to partition three ways.
+    three_way: bool,
+    /// Shortest side of a downwave split `drive_rayon` hands to a task.
+    #[cfg(feature = "rayon")]
+    parallel_threshold: usize,
+}
+
+/// Finishes a range of at most `threshold` elements, with `insert` past the
+/// sorting networks.
+fn sort_leaf<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    ctx: &mut Ctx<'_, T, F, C>,
+    insert: InsertionSort<T, F, C>,
+) {
+    if let Some(small_sort) = ctx.small_sort
+        && small_sort(arr)
+    {
+        return;
+    }
+    if arr.len() <= GENERIC_NETWORK_MAX {
+        network_sort(arr, ctx.compare, ctx.counter);
+        return;
+    }
+    insert(arr, ctx.compare, ctx.counter);
+}
+
+/// `insertion_sort` or `binary_insertion_sort`.
+type InsertionSort<T, F, C> = fn(&mut [T], &mut F, &mut C);
+
+/// Compare-exchange networks for up to 16 elements, indexed by length, each
+/// with the fewest exchanges known for its length. Each pair `(i, j)` puts
+/// the smaller of `arr[i]` and `arr[j]` at `i`. The 15-element network is
+/// Green's 16-element one without the exchanges that touch index 15.
+#[rustfmt::skip]
+const NETWORKS: [&[(u8, u8)]; 17] = [
+    &[],
+    &[],
+    &[(0, 1)],
+    &[(0, 2), (0, 1), (1, 2)],
+    &[(0, 1), (2, 3), (0, 2), (1, 3), (1, 2)],
+    &[
+        (0, 3), (1, 4), (0, 2), (1, 3), (0, 1), (2, 4), (1, 2), (3, 4), (2, 3),
+    ],
+    &[
+        (0, 5), (1, 3), (2, 4), (1, 2), (3, 4), (0, 3), (2, 5), (0, 1), (2, 3),
+        (4, 5), (1, 2), (3, 4),
+    ],
+    &[
+        (0, 6), (2, 3), (4, 5), (0, 2), (1, 4), (3, 6), (0, 1), (2, 5), (3, 4),
+        (1, 2), (4, 6), (2, 3), (4, 5), (1, 2), (3, 4), (5, 6),
+    ],
+    &[
+        (0, 2), (1, 3), (4, 6), (5, 7), (0, 4), (1, 5), (2, 6), (3, 7), (0, 1),
+        (2, 3), (4, 5), (6, 7), (2, 4), (3, 5), (1, 4), (3, 6), (1, 2), (3, 4),
+        (5, 6),
+    ],
+    &[
+        (0, 3), (1, 7), (2, 5), (4, 8), (0, 7), (2, 4), (3, 8), (5, 6), (0, 2),
+        (1, 3), (4, 5), (7, 8), (1, 4), (3, 6), (5, 7), (0, 1), (2, 4), (3, 5),
+        (6, 8), (2, 3), (4, 5), (6, 7), (1, 2), (3, 4), (5, 6),
+    ],
+    &[
+        (0, 8), (1, 9), (2, 7), (3, 5), (4, 6), (0, 2), (1, 4), (5, 8), (7, 9),
+        (0, 3), (2, 4), (5, 7), (6, 9), (0, 1), (3, 6), (8, 9), (1, 5), (2, 3),
+        (4, 8), (6, 7), (1, 2), (3, 5), (4, 6), (7, 8), (2, 3), (4, 5), (6, 7),
+        (3, 4), (5, 6),
+    ],
+    &[
+        (0, 9), (1, 6), (2, 4), (3, 7), (5, 8), (0, 1), (3, 5), (4, 10), (6, 9),
+        (7, 8), (1, 3), (2, 5), (4, 7), (8, 10), (0, 4), (1, 2), (3, 7), (5, 9),
+        (6, 8), (0, 1), (2, 6), (4, 5), (7, 8), (9, 10), (2, 4), (3, 6), (5, 7),
+        (8, 9), (1, 2), (3, 4), (5, 6), (7, 8), (2, 3), (4, 5), (6, 7),
+    ],
+    &[
+        (0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9), (0, 1), (2, 5),
+        (3, 4), (6, 9), (7, 8), (10, 11), (0, 2), (1, 6), (5, 10), (9, 11),
+        (0, 3), (1, 2), (4, 6), (5, 7), (8, 11), (9, 10), (1, 4), (3, 5),
+        (6, 8), (7, 10), (1, 3), (2, 5), (6, 9), (8, 10), (2, 3), (4, 5),
+        (6, 7), (8, 9), (4, 6), (5, 7), (3, 4), (5, 6), (7, 8),
+    ],
+    &[
+        (0, 12), (1, 10), (2, 9), (3, 7), (5, 11), (6, 8), (1, 6), (2, 3),
+        (4, 11), (7, 9), (8, 10), (0, 4), (1, 2), (3, 6), (7, 8), (9, 10),
+        (11, 12), (4, 6), (5, 9), (8, 11), (10, 12), (0, 5), (3, 8), (4, 7),
+        (6, 11), (9, 10), (0, 1), (2, 5), (6, 9), (7, 8), (10, 11), (1, 3),
+        (2, 4), (5, 6), (9, 10), (1, 2), (3, 4), (5, 7), (6, 8), (2, 3), (4, 5),
+        (6, 7), (8, 9), (3, 4), (5, 6),
+    ],
+    &[
+        (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (0, 2),
+        (1, 3), (4, 8), (5, 9), (10, 12), (11, 13), (0, 4), (1, 2), (3, 7),
+        (5, 8), (6, 10), (9, 13), (11, 12), (0, 6), (1, 5), (3, 9), (4, 10),
+        (7, 13), (8, 12), (2, 10), (3, 11), (4, 6), (7, 9), (1, 3), (2, 8),
+        (5, 11), (6, 7), (10, 12), (1, 4), (2, 6), (3, 5), (7, 11), (8, 10),
+        (9, 12), (2, 4), (3, 6), (5, 8), (7, 10), (9, 11), (3, 4), (5, 6),
+        (7, 8), (9, 10), (6, 7),
+    ],
+    &[
+        (0, 13), (1, 12), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10), (0, 5),
+        (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (11, 12), (0, 1), (2, 3),
+        (4, 5), (6, 8), (7, 9), (10, 11), (12, 13), (0, 2), (1, 3), (4, 10),
+        (5, 11), (6, 7), (8, 9), (12, 14), (1, 2), (3, 12), (4, 6), (5, 7),
+        (8, 10), (9, 11), (13, 14), (1, 4), (2, 6), (5, 8), (7, 10), (9, 13),
+        (11, 14), (2, 4), (3, 6), (9, 12), (11, 13), (3, 5), (6, 8), (7, 9),
+        (10, 12), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (6, 7), (8, 9),
+    ],
+    &[
+        (0, 13), (1, 12), (2, 15), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10),
+        (0, 5), (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (10, 15), (11, 12),
+        (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13), (14, 15),
+        (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9), (12, 14), (13, 15),
+        (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14), (1, 4),
+        (2, 6), (5, 8), (7, 10), (9, 13), (11, 14), (2, 4), (3, 6), (9, 12),
+        (11, 13), (3, 5), (6, 8), (7, 9), (10, 12), (3, 4), (5, 6), (7, 8),
+        (9, 10), (11, 12), (6, 7), (8, 9),
+    ],
+];
+
+/// Longest leaf `network_sort` takes, for any element type and comparator:
+/// every length in `NETWORKS`. Up to 16 elements a network makes about as
+/// many comparisons as binary insertion sort and never mispredicts, so it
+/// is faster for `i32`s (about 3× on isolated leaves) and no slower for
+/// `String`s.
+const GENERIC_NETWORK_MAX: usize = NETWORKS.len() - 1;
+
+/// Sorts fewer than `NETWORKS.len()` elements with a fixed network. Unlike
+/// insertion sort the sequence of comparisons does not depend on the data,
+/// and each exchange is a branch-free select, which suits tiny leaves where
+/// insertion sort's inner loop mispredicts.
+fn network_sort<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    compare: &mut F,
+    counter: &mut C,
+) {
+    let network = NETWORKS[arr.len()];
+    let ptr = arr.as_mut_ptr();
+    for &(i, j) in network {
+        counter.compared();
+        // SAFETY: every network only indexes below its own length.
+        unsafe { exchange(ptr.add(i as usize), ptr.add(j as usize), compare, counter) }
+    }
+}
+
+/// Sorts up to 16 `i32`s with the network for their length, each exchange
+/// a branch-free `min` and `max`.
+///
+/// # Panics
+///
+/// Panics if `arr` holds more than 16 elements.
+#[inline]
+pub(crate) fn sort_network(arr: &mut [i32]) {
+    let network = NETWORKS[arr.len()];
+    let ptr = arr.as_mut_ptr();
+    for &(i, j) in network {
+        // SAFETY: every network only indexes below its own length.
+        unsafe {
+            let (a, b) = (ptr.add(i as usize), ptr.add(j as usize));
+            let (x, y) = (*a, *b);
+            *a = x.min(y);
+            *b = x.max(y);
+        }
+    }
+}
+
+/// [`sort_network`] as the leaf sorter of `sort_counted`, declining runs of
+/// more than 16 elements.
+pub(crate) fn small_sort_network(arr: &mut [i32]) -> bool {
+    if arr.len() >= NETWORKS.len() {
+        return false;
+    }
+    sort_network(arr);
+    true
+}
+
+/// Orders `*a` and `*b` without branching on the comparison.
+///
+/// The comparison runs before anything moves, so a panic in `compare`
+/// leaves both elements in place.
+///
+/// # Safety
+///
+/// `a` and `b` must be distinct and valid for reads and writes.
+#[inline(always)]
+unsafe fn exchange<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    a: *mut T,
+    b: *mut T,
+    compare: &mut F,
+    counter: &mut C,
+) {
+    unsafe {
+        let swap = compare(&*b, &*a) == Ordering::Less;
+        if swap {
+            counter.swapped();
+        }
+        let lo = hint::select_unpredictable(swap, b, a);
+        let hi = ManuallyDrop::new(ptr::read(hint::select_unpredictable(swap, a, b)));
+        ptr::copy(lo, a, 1);
+        ptr::copy_nonoverlapping(&*hi, b, 1);
+    }
+}
+
+pub(crate) fn insertion_sort<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    compare: &mut F,
+    counter: &mut C,
+) {
+    let len = arr.len();
+    if len < 2 {
+        return;
+    }
+    for i in 1..len {
+        unsafe {
+            let ptr = arr.as_mut_ptr();
+            // Lift the key out of the slice; the hole is refilled on drop,
+            // even if a comparison panics.
+            let key = ManuallyDrop::new(ptr::read(ptr.add(i)));
+            let mut hole = InsertionHole {
+                src: &*key,
+                dest: ptr.add(i),
+            };
+            let mut j = i;
+            while j > 0 && {
+                counter.compared();
+                compare(&*ptr.add(j - 1), &key) == Ordering::Greater
+            } {
+                ptr::copy_nonoverlapping(ptr.add(j - 1), ptr.add(j), 1);
+                j -= 1;
+                hole.dest = ptr.add(j);
+            }
+        }
+    }
+}
+
+/// Insertion sort that finds each insertion point with a branchless binary
+/// search and moves the larger elements up with one `ptr::copy`, instead of
+/// comparing and shifting them one at a time.
+///
+/// An element already in place costs one comparison, and one displaced far
+/// costs a logarithmic number of them with no mispredicted loop exit. Each
+/// element lands after every equal one, so the sort is stable.
+fn binary_insertion_sort<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    compare: &mut F,
+    counter: &mut C,
+) {
+    let ptr = arr.as_mut_ptr();
+    for i in 1..arr.len() {
+        // SAFETY: every index is at most `i`. Nothing moves until the search
+        // is done, so a panicking comparison leaves `arr` as it was.
+        unsafe {
+            let key = ptr.add(i);
+            counter.compared();
+            if compare(&*ptr.add(i - 1), &*key) != Ordering::Greater {
+                continue;
+            }
+            // The insertion point is in `base..base + size`: past every
+            // element `<=` the key, and before `arr[i - 1]`.
+            let mut base = 0;
+            let mut size = i;
+            while size > 1 {
+                let half = size / 2;
+                let mid = base + half;
+                counter.compared();
+                let after = compare(&*ptr.add(mid - 1), &*key) != Ordering::Greater;
+                base = hint::select_unpredictable(after, mid, base);
+                size -= half;
+            }
+            let tmp = ManuallyDrop::new(ptr::read(key));
+            ptr::copy(ptr.add(base), ptr.add(base + 1), i - base);
+            ptr::copy_nonoverlapping(&*tmp, ptr.add(base), 1);
+        }
+    }
+}
+
+/// Writes `src` into `dest` when dropped.
+struct InsertionHole<T> {
+    src: *const T,
+    dest: *mut T,
+}
+
+impl<T> Drop for InsertionHole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::copy_nonoverlapping(self.src, self.dest, 1);
+        }
+    }
+}
+
+/// In-place binary-heap sort: builds a max-heap, then repeatedly swaps the
+/// root behind the shrinking heap. The fallback for too-deep recursion.
+pub fn heapsort<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    compare: &mut F,
+    counter: &mut C,
+) {
+    let len = arr.len();
+    for node in (0..len / 2).rev() {
+        sift_down(arr, node, len, compare, counter);
+    }
+    for end in (1..len).rev() {
+        counter.swapped();
+        arr.swap(0, end);
+        sift_down(arr, 0, end, compare, counter);
+    }
+}
+
+fn sift_down<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    mut node: usize,
+    len: usize,
+    compare: &mut F,
+    counter: &mut C,
+) {
+    loop {
+        let mut child = 2 * node + 1;
+        if child >= len {
+            return;
+        }
+        if child + 1 < len {
+            counter.compared();
+            if compare(&arr[child], &arr[child + 1]) == Ordering::Less {
+                child += 1;
+            }
+        }
+        counter.compared();
+        if compare(&arr[node], &arr[child]) != Ordering::Less {
+            return;
+        }
+        counter.swapped();
+        arr.swap(node, child);
+        node = child;
+    }
+}
+
+/// Rotates `arr[m..=p]` left by `r - m`, moving the block `arr[r..=p]` in
+/// front of `arr[m..r]`. This is the only primitive `merge_by` and the wave
+/// recursion use to move elements between runs.
+#[inline(always)]
+fn block_swap<T>(arr: &mut [T], m: usize, r: usize, p: usize) {
+    let left_len = r.wrapping_sub(m);
+    if left_len == 0 {
+        return;
+    }
+    let range_len = p - m + 1;
+    arr[m..m + range_len].rotate_left(left_len);
+}
+
+/// Merges the sorted runs `arr[..mid]` and `arr[mid..]` in place.
+///
+/// Recursively splits both runs around a binary-searched cut and joins the
+/// middle pieces with `block_swap`, so no buffer is needed.
+pub fn merge_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], mid: usize, compare: &mut F) {
+    let len = arr.len();
+    let left_len = mid;
+    let right_len = len - mid;
+    if left_len == 0 || right_len == 0 {
+        return;
+    }
+    if len == 2 {
+        if compare(&arr[1], &arr[0]) == Ordering::Less {
+            arr.swap(0, 1);
+        }
+        return;
+    }
+    let (left_cut, right_cut) = if left_len > right_len {
+        let left_cut = left_len / 2;
+        let right_cut =
+            mid + arr[mid..].partition_point(|x| compare(x, &arr[left_cut]) == Ordering::Less);
+        (left_cut, right_cut)
+    } else {
+        let right_cut = mid + right_len / 2;
+        let left_cut =
+            arr[..mid].partition_point(|x| compare(x, &arr[right_cut]) != Ordering::Greater);
+        (left_cut, right_cut)
+    };
+    if right_cut > left_cut {
+        block_swap(arr, left_cut, mid, right_cut - 1);
+    }
+    let new_mid = left_cut + (right_cut - mid);
+    let (lo, hi) = arr.split_at_mut(new_mid);
+    merge_by(lo, left_cut, compare);
+    merge_by(hi, mid - left_cut, compare);
+}
+
+/// Stable sort: insertion-sorts runs of `threshold` elements, then merges
+/// neighbouring runs bottom-up with `merge_by`. Both steps keep equal
+/// elements in their original order.
+pub fn sort_stable_by<T, F: FnMut(&T, &T) -> Ordering>(
+    arr: &mut [T],
+    threshold: usize,
+    compare: &mut F,
+) {
+    let n = arr.len();
+    let run = threshold.max(1);
+    for chunk in arr.chunks_mut(run) {
+        insertion_sort(chunk, compare, &mut ());
+    }
+    let mut width = run;
+    while width < n {
+        for chunk in arr.chunks_mut(2 * width) {
+            if chunk.len() > width {
+                merge_by(chunk, width, compare);
+            }
+        }
+        width = width.saturating_mul(2);
+    }
+}
+
+/// How many elements ahead of each cursor `partition` prefetches.
+const PREFETCH_AHEAD: usize = 64;
+
+/// Hints the CPU to pull the cache line at `p` into L1. `p` may point
+/// outside the slice: prefetches never fault and the pointer is not
+/// dereferenced.
+#[inline(always)]
+fn prefetch<T>(p: *const T) {
+    // PREFETCHT0 is part of SSE, which every x86-64 CPU has, so no runtime
+    // check is needed. Other targets keep the plain scalar loop.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    unsafe {
+        use core::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
+        _mm_prefetch::<_MM_HINT_T0>(p as *const i8);
+    }
+    #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+    let _ = p;
+}
+
+fn partition<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    l: usize,
+    r: usize,
+    p_idx: usize,
+    compare: &mut F,
+    counter: &mut C,
+) -> usize {
+    // The scan reads `arr[l]` before comparing `i` with `j`, so the range
+    // must be non-empty.
+    debug_assert!(l < r && r < arr.len());
+    debug_assert!(p_idx < arr.len() && (p_idx < l || p_idx >= r));
+    // Panic safety: elements only move by whole `ptr::swap`s made between
+    // comparisons, so a panicking `compare` leaves `arr` a permutation of its
+    // input and needs no drop guard. `insertion_sort` is the one place an
+    // element is lifted out of the slice, and `InsertionHole` puts it back.
+    let m = unsafe {
+        let ptr = arr.as_mut_ptr();
+        // The pivot lives outside `l..r`, so it is never moved by the swaps.
+        let pivot_val = &*ptr.add(p_idx);
+        let mut i = l;
+        let mut j = r;
+        'scan: loop {
+            loop {
+                let val = &*ptr.add(i);
+                counter.compared();
+                if compare(val, pivot_val) != Ordering::Less {
+                    break;
+                }
+                i += 1;
+                if i == j {
+                    break 'scan i;
+                }
+                prefetch(ptr.wrapping_add(i + PREFETCH_AHEAD));
+            }
+            loop {
+                if j == i {
+                    break 'scan i;
+                }
+                j -= 1;
+                prefetch(ptr.wrapping_sub(PREFETCH_AHEAD).wrapping_add(j));
+                let val = &*ptr.add(j);
+                counter.compared();
+                if compare(val, pivot_val) != Ordering::Greater {
+                    break;
+                }
+            }
+            counter.swapped();
+            ptr::swap(ptr.add(i), ptr.add(j));
+        }
+    };
+    debug_assert!(l <= m && m <= r);
+    m
+}
+
+/// Partitions `arr` around `arr[pivot]` and returns the pivot's final index.
+/// Requires `pivot < arr.len()`.
+pub fn partition_at<T, F: FnMut(&T, &T) -> Ordering>(
+    arr: &mut [T],
+    pivot: usize,
+    compare: &mut F,
+) -> usize {
+    // Park the pivot at the end so it sits outside the range `partition`
+    // scans, then drop it into its final slot.
+    let last = arr.len() - 1;
+    arr.swap(pivot, last);
+    if last == 0 {
+        return 0;
+    }
+    let m = partition(arr, 0, last, last, compare, &mut ());
+    arr.swap(m, last);
+    m
+}
+
+/// Quickselect: reorders `arr` so that `arr[n]` holds the element that would
+/// be there after sorting, with everything before it `<=` and everything
+/// after it `>=`. Requires `n < arr.len()`.
+pub fn select_nth_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], n: usize, compare: &mut F) {
+    if arr.len() < 2 {
+        return;
+    }
+    let mut lo = 0;
+    let mut hi = arr.len();
+    while hi - lo > 1 {
+        let m = lo + partition_at(&mut arr[lo..hi], (hi - lo) / 2, compare);
+        match m.cmp(&n) {
+            Ordering::Equal => return,
+            Ordering::Less => lo = m + 1,
+            Ordering::Greater => hi = m,
+        }
+    }
+}
+
+/// Unsorted parts shorter than this take the middle of the sorted block as
+/// the pivot without sampling.
+const SAMPLE_MIN: usize = 256;
+
+/// Picks the pivot index in the sorted block `arr[sorted_start..=end]`.
+///
+/// The block is sorted, so its middle is already its median; what matters is
+/// how evenly the pivot splits the unsorted `arr[start..sorted_start]`. For
+/// large unsorted parts, take the median of three samples from it and
+/// binary-search its position in the sorted block, clamped to the middle
+/// half so the sorted block is still split reasonably evenly. The result is
+/// below `end` unless the block has a single element.
+fn choose_pivot<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &[T],
+    start: usize,
+    sorted_start: usize,
+    end: usize,
+    ctx: &mut Ctx<'_, T, F, C>,
+) -> usize {
+    if sorted_start - start < SAMPLE_MIN {
+        return sorted_start + (end - sorted_start) / 2;
+    }
+    let a = start;
+    let b = start + (sorted_start - start) / 2;
+    let c = sorted_start - 1;
+    let median = median_of_three(arr, a, b, c, ctx);
+    let sorted = &arr[sorted_start..=end];
+    let lo = sorted.partition_point(|x| {
+        ctx.counter.compared();
+        (ctx.compare)(x, &arr[median]) == Ordering::Less
+    });
+    let hi = lo
+        + sorted[lo..].partition_point(|x| {
+            ctx.counter.compared();
+            (ctx.compare)(x, &arr[median]) != Ordering::Greater
+        });
+    // Land in the middle of any run of equal keys
+    let offset = lo + (hi - lo) / 2;
+    let len = end - sorted_start;
+    sorted_start
+        + offset
+            .clamp(len / 4, len - len / 4)
+            .min(len.saturating_sub(1))
+}
+
+fn median_of_three<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &[T],
+    a: usize,
+    b: usize,
+    c: usize,
+    ctx: &mut Ctx<'_, T, F, C>,
+) -> usize {
+    let mut less = |x: usize, y: usize| {
+        ctx.counter.compared();
+        (ctx.compare)(&arr[x], &arr[y]) == Ordering::Less
+    };
+    if less(a, b) {
+        if less(b, c) {
+            b
+        } else if less(a, c) {
+            c
+        } else {
+            a
+        }
+    } else if less(a, c) {
+        a
+    } else if less(b, c) {
+        c
+    } else {
+        b
+    }
+}
+
+/// One pending call of the wave recursion.
+///
+/// Each step of `downwave` and `upwave` does its partitioning or merging in
+/// place and returns the calls it would make next, in order, as `Wave`s.
+/// `drive` runs them off an explicit work stack so the native stack depth
+/// stays constant however deep the recursion goes; `drive_recursive` runs
+/// the same steps as plain recursion.
+#[derive(Clone, Copy)]
+enum Wave {
+    /// Merge the unsorted `arr[start..sorted_start]` into the sorted
+    /// `arr[sorted_start..=end]`.
+    Down {
+        start: usize,
+        sorted_start: usize,
+        end: usize,
+        depth: u32,
+    },
+    /// Sort `arr[start..=end]` by growing a sorted suffix.
+    Up {
+        start: usize,
+        end: usize,
+        depth: u32,
+    },
+    /// One `merge_chunk` of an upwave over `arr[start..=end]`, merging the
+    /// chunk from `left_bound` into the suffix at `sorted_start`. The upwave
+    /// continues with the next chunk unless this is the `last`.
+    Chunk {
+        start: usize,
+        left_bound: usize,
+        sorted_start: usize,
+        end: usize,
+        depth: u32,
+        last: bool,
+    },
+}
+
+#[cfg(feature = "rayon")]
+impl Wave {
+    /// First and last index the wave touches.
+    fn bounds(self) -> (usize, usize) {
+        match self {
+            Wave::Down { start, end, .. }
+            | Wave::Up { start, end, .. }
+            | Wave::Chunk { start, end, .. } => (start, end),
+        }
+    }
+
+    /// The same wave on `arr[by..]`.
+    fn shifted(self, by: usize) -> Wave {
+        match self {
+            Wave::Down {
+                start,
+                sorted_start,
+                end,
+                depth,
+            } => Wave::Down {
+                start: start - by,
+                sorted_start: sorted_start - by,
+                end: end - by,
+                depth,
+            },
+            Wave::Up { start, end, depth } => Wave::Up {
+                start: start - by,
+                end: end - by,
+                depth,
+            },
+            Wave::Chunk {
+                start,
+                left_bound,
+                sorted_start,
+                end,
+                depth,
+                last,
+            } => Wave::Chunk {
+                start: start - by,
+                left_bound: left_bound - by,
+                sorted_start: sorted_start - by,
+                end: end - by,
+                depth,
+                last,
+            },
+        }
+    }
+}
+
+/// Calls made by one step, to be run first to last.
+type Next = [Option<Wave>; 2];
+
+const DONE: Next = [None, None];
+
+/// Runs `wave` and everything it leads to, depth first.
+#[cfg(feature = "std")]
+fn drive<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    wave: Wave,
+    ctx: &mut Ctx<'_, T, F, C>,
+) {
+    let mut stack = WorkStack::new();
+    stack.push(wave);
+    while let Some(wave) = stack.pop() {
+        // Push in reverse so the first call is popped first
+        for next in step(arr, wave, ctx).into_iter().rev().flatten() {
+            stack.push(next);
+        }
+    }
+}
+
+/// Pending waves kept inline before `WorkStack` spills to the heap. Sorts of
+/// 10M random or adversarial elements peak around 30, so the spill is only
+/// reached by unusually deep inputs and sorting stays allocation free.
+#[cfg(feature = "std")]
+const INLINE_WAVES: usize = 64;
+
+/// LIFO stack of pending waves: a fixed inline array, then a `Vec` once
+/// that is full. The spilled waves are always the most recent ones.
+#[cfg(feature = "std")]
+struct WorkStack {
+    inline: [Wave; INLINE_WAVES],
+    len: usize,
+    spill: Vec<Wave>,
+}
+
+#[cfg(feature = "std")]
+impl WorkStack {
+    fn new() -> Self {
+        WorkStack {
+            inline: [Wave::Up {
+                start: 0,
+                end: 0,
+                depth: 0,
+            }; INLINE_WAVES],
+            len: 0,
+            spill: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, wave: Wave) {
+        if self.len < INLINE_WAVES {
+            self.inline[self.len] = wave;
+            self.len += 1;
+        } else {
+            self.spill.push(wave);
+        }
+    }
+
+    fn pop(&mut self) -> Option<Wave> {
+        if let Some(wave) = self.spill.pop() {
+            return Some(wave);
+        }
+        self.len = self.len.checked_sub(1)?;
+        Some(self.inline[self.len])
+    }
+}
+
+/// Without `std` there is no `Vec` for the work stack, so the steps recurse.
+#[cfg(not(feature = "std"))]
+use self::drive_recursive as drive;
+
+/// Runs `wave` like `drive`, but on the native stack.
+fn drive_recursive<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    wave: Wave,
+    ctx: &mut Ctx<'_, T, F, C>,
+) {
+    for next in step(arr, wave, ctx).into_iter().flatten() {
+        drive_recursive(arr, next, ctx);
+    }
+}
+
+/// Runs `wave` like `drive_recursive`, but joins the two calls of a
+/// downwave split with `rayon::join` once both sides hold at least
+/// `parallel_threshold` elements. The sides are disjoint ranges with the
+/// pivot between them, so each task gets its own piece of `arr` and its own
+/// `Ctx`. Upwave chunks depend on each other and stay in order.
+#[cfg(feature = "rayon")]
+fn drive_rayon<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(
+    arr: &mut [T],
+    wave: Wave,
+    ctx: &mut Ctx<'_, T, &F, ()>,
+) {
+    let next = step(arr, wave, ctx);
+    if let (Wave::Down { .. }, [Some(left), Some(right)]) = (wave, next) {
+        let (left_start, left_end) = left.bounds();
+        let (right_start, right_end) = right.bounds();
+        let min = ctx.parallel_threshold;
+        if left_end - left_start + 1 >= min && right_end - right_start + 1 >= min {
+            let compare: &F = ctx.compare;
+            let (small_sort, threshold, limit, three_way) =
+                (ctx.small_sort, ctx.threshold, ctx.limit, ctx.three_way);
+            let task = |arr: &mut [T], wave: Wave| {
+                let mut compare = compare;
+                let mut ctx = Ctx {
+                    compare: &mut compare,
+                    counter: &mut (),
+                    small_sort,
+                    threshold,
+                    limit,
+                    cancel: None,
+                    cancelled: false,
+                    three_way,
+                    parallel_threshold: min,
+                };
+                drive_rayon(arr, wave, &mut ctx);
+            };
+            let (lo, hi) = arr.split_at_mut(right_start);
+            rayon::join(|| task(lo, left), || task(hi, right.shifted(right_start)));
+            return;
+        }
+    }
+    for next in next.into_iter().flatten() {
+        drive_rayon(arr, next, ctx);
+    }
+}
+
+fn step<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    wave: Wave,
+    ctx: &mut Ctx<'_, T, F, C>,
+) -> Next {
+    if let Some(cancel) = ctx.cancel
+        && cancel.load(atomic::Ordering::Relaxed)
+    {
+        ctx.cancelled = true;
+    }
+    if ctx.cancelled {
+        return DONE;
+    }
+    match wave {
+        Wave::Down {
+            start,
+            sorted_start,
+            end,
+            depth,
+        } => downwave(arr, start, sorted_start, end, ctx, depth),
+        Wave::Up { start, end, depth } => upwave(arr, start, end, ctx, depth),
+        Wave::Chunk {
+            start,
+            left_bound,
+            sorted_start,
+            end,
+            depth,
+            last,
+        } => {
+            let (bound, down) = merge_chunk(arr, start, left_bound, sorted_start, end, ctx, depth);
+            [
+                down,
+                if last {
+                    None
+                } else {
+                    upwave_next(start, bound, end, depth)
+                },
+            ]
+        }
+    }
+}
+
+fn downwave<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    start: usize,
+    sorted_start: usize,
+    end: usize,
+    ctx: &mut Ctx<'_, T, F, C>,
+    depth: u32,
+) -> Next {
+    debug_assert!(start <= sorted_start && sorted_start <= end && end < arr.len());
+    if sorted_start == start {
+        return DONE;
+    }
+    trace!("[downwave {start} {sorted_start} {end}]");
+    ctx.counter.reached_depth(ctx.limit - depth);
+    if end - start <= ctx.threshold {
+        sort_leaf(&mut arr[start..=end], ctx, insertion_sort);
+        return DONE;
+    }
+    if depth == 0 {
+        heapsort(&mut arr[start..=end], ctx.compare, ctx.counter);
+        return DONE;
+    }
+    let depth = depth - 1;
+    let down = |start, sorted_start, end| Wave::Down {
+        start,
+        sorted_start,
+        end,
+        depth,
+    };
+    let up = |start, end| Wave::Up { start, end, depth };
+    let p = choose_pivot(arr, start, sorted_start, end, ctx);
+    if ctx.three_way
+        && let Some((eq_lo, eq_hi)) = pivot_run(arr, sorted_start, end, p, ctx)
+    {
+        return downwave_three_way(arr, start, sorted_start, end, eq_lo, eq_hi, ctx, depth);
+    }
+    let m = partition(arr, start, sorted_start, p, ctx.compare, ctx.counter);
+    trace!("[partition -> {m}]");
+    // The unsorted elements `>= pivot` now fill `m..sorted_start`. Rotating
+    // them behind the pivot leaves
+    //   [start..m: U<][m..split: S<][split: pivot][split+1..=p: U>][p+1..=end: S>]
+    // with `U` from the unsorted part and `S` from the sorted block.
+    if m < sorted_start {
+        block_swap(arr, m, sorted_start, p);
+    }
+    let split = m + (p - sorted_start);
+    // Each side merges its unsorted piece into its sorted one, and is done
+    // if it has no unsorted piece. If the pivot was the first key of the
+    // sorted block, `S<` is empty and both sides go to an upwave instead.
+    let left = if m == start {
+        None
+    } else if split == m {
+        Some(up(start, m - 1))
+    } else {
+        Some(down(start, m, split - 1))
+    };
+    let right = if m == sorted_start {
+        None
+    } else if p == sorted_start {
+        Some(up(split + 1, end))
+    } else {
+        Some(down(split + 1, p + 1, end))
+    };
+    [left, right]
+}
+
+/// Pivots repeated at least this often in the sorted block switch
+/// `downwave` to the three-way partition.
+const THREE_WAY_MIN_RUN: usize = 64;
+
+/// Keys `Partition::Auto` samples to decide between two- and three-way
+/// partitioning.
+const DUPLICATE_PROBES: usize = 64;
+
+/// `Partition::Auto`: whether `DUPLICATE_PROBES` evenly spaced keys of `arr`
+/// contain a repeat. Slices too short for a pivot to repeat
+/// `THREE_WAY_MIN_RUN` times per probe count as having duplicates, which
+/// keeps the per-pivot check and skips the sample.
+///
+/// Only a false `false` is costly (a key repeated throughout then splits two
+/// ways, about 5× slower with two distinct keys), so any repeat in the
+/// sample keeps three-way partitioning on. 10 000 distinct keys among
+/// millions rarely repeat in the sample, and two-way is a little faster
+/// there anyway (`cargo bench --bench duplicates`).
+fn has_duplicates<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &[T],
+    compare: &mut F,
+    counter: &mut C,
+) -> bool {
+    let n = arr.len();
+    if n < DUPLICATE_PROBES * THREE_WAY_MIN_RUN {
+        return true;
+    }
+    let step = n / DUPLICATE_PROBES;
+    let mut probes: [&T; DUPLICATE_PROBES] = core::array::from_fn(|i| &arr[i * step + step / 2]);
+    binary_insertion_sort(&mut probes, &mut |a: &&T, b: &&T| compare(a, b), counter);
+    probes.windows(2).any(|w| {
+        counter.compared();
+        compare(w[0], w[1]) == Ordering::Equal
+    })
+}
+
+/// Returns the run `eq_lo..eq_hi` of keys equal to `arr[p]` in the sorted
+/// block `arr[sorted_start..=end]` if it is at least `THREE_WAY_MIN_RUN`
+/// long. Both neighbours of `p` are checked first, so pivots without
+/// duplicates cost at most two extra comparisons.
+fn pivot_run<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &[T],
+    sorted_start: usize,
+    end: usize,
+    p: usize,
+    ctx: &mut Ctx<'_, T, F, C>,
+) -> Option<(usize, usize)> {
+    let mut equal = |i: usize| {
+        ctx.counter.compared();
+        (ctx.compare)(&arr[i], &arr[p]) == Ordering::Equal
+    };
+    if !(p < end && equal(p + 1) || p > sorted_start && equal(p - 1)) {
+        return None;
+    }
+    let pivot = &arr[p];
+    let eq_lo = sorted_start
+        + arr[sorted_start..p].partition_point(|x| {
+            ctx.counter.compared();
+            (ctx.compare)(x, pivot) == Ordering::Less
+        });
+    let eq_hi = p
+        + 1
+        + arr[p + 1..=end].partition_point(|x| {
+            ctx.counter.compared();
+            (ctx.compare)(x, pivot) != Ordering::Greater
+        });
+    (eq_hi - eq_lo >= THREE_WAY_MIN_RUN).then_some((eq_lo, eq_hi))
+}
+
+/// `downwave` around a pivot with many duplicates, whose equal keys fill
+/// `arr[eq_lo..eq_hi]` of the sorted block.
+///
+/// The unsorted part is split three ways (Dijkstra's Dutch national flag)
+/// into `< pivot`, `== pivot` and `> pivot`. Two rotations then line up
+/// `[U<][S<][U=][S=][U>][S>]`, where `S` is the sorted block: the equal keys
+/// in the middle are final and only the outer pairs are merged further.
+#[allow(clippy::too_many_arguments)]
+fn downwave_three_way<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    start: usize,
+    sorted_start: usize,
+    end: usize,
+    eq_lo: usize,
+    eq_hi: usize,
+    ctx: &mut Ctx<'_, T, F, C>,
+    depth: u32,
+) -> Next {
+    let (unsorted, sorted) = arr.split_at_mut(sorted_start);
+    let pivot = &sorted[eq_lo - sorted_start];
+    let mut lt = start;
+    let mut i = start;
+    let mut gt = sorted_start;
+    while i < gt {
+        ctx.counter.compared();
+        match (ctx.compare)(&unsorted[i], pivot) {
+            Ordering::Less => {
+                ctx.counter.swapped();
+                unsorted.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                ctx.counter.swapped();
+                unsorted.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    trace!("[partition -> {lt}..{gt}]");
+    // [U<][U=][U>][S<][S=][S>] -> [U<][S<][U=][U>][S=][S>]
+    let less = eq_lo - sorted_start;
+    if less > 0 && lt < sorted_start {
+        block_swap(arr, lt, sorted_start, eq_lo - 1);
+    }
+    // -> [U<][S<][U=][S=][U>][S>]
+    let greater_start = gt + less;
+    if greater_start < eq_lo {
+        block_swap(arr, greater_start, eq_lo, eq_hi - 1);
+    }
+    let right = greater_start + (eq_hi - eq_lo);
+    let depth = depth - 1;
+    let side = |start: usize, sorted_start: usize, end: usize| {
+        if sorted_start <= end {
+            (start < sorted_start).then_some(Wave::Down {
+                start,
+                sorted_start,
+                end,
+                depth,
+            })
+        } else {
+            (start < end).then_some(Wave::Up { start, end, depth })
+        }
+    };
+    [
+        (lt + less > start)
+            .then(|| side(start, lt, lt + less - 1))
+            .flatten(),
+        (right <= end).then(|| side(right, eq_hi, end)).flatten(),
+    ]
+}
+
+fn upwave<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    start: usize,
+    end: usize,
+    ctx: &mut Ctx<'_, T, F, C>,
+    depth: u32,
+) -> Next {
+    debug_assert!(start <= end && end < arr.len());
+    if start == end {
+        return DONE;
+    }
+    trace!("[upwave {start}..{end}]");
+    if end - start <= ctx.threshold {
+        // An upwave range is still in input order, so a nearly sorted input
+        // leaves a few elements displaced far: the binary search finds their
+        // place without walking the whole run. Downwave and whole-slice
+        // leaves are mostly random, where the linear walk measured faster.
+        sort_leaf(&mut arr[start..=end], ctx, binary_insertion_sort);
+        return DONE;
+    }
+    step(
+        arr,
+        Wave::Chunk {
+            start,
+            left_bound: end - 1,
+            sorted_start: end,
+            end,
+            depth,
+            last: false,
+        },
+        ctx,
+    )
+}
+
+/// The upwave step after a chunk grew the sorted suffix of `arr[start..=end]`
+/// to begin at `sorted_start`: expand by the next chunk while the suffix is
+/// under a quarter of the range, then merge whatever is left in one go.
+fn upwave_next(start: usize, sorted_start: usize, end: usize, depth: u32) -> Option<Wave> {
+    let total_len = end - start + 1;
+    let sorted_len = end - sorted_start + 1;
+    let chunk = |left_bound, last| Wave::Chunk {
+        start,
+        left_bound,
+        sorted_start,
+        end,
+        depth,
+        last,
+    };
+    if total_len >= sorted_len.saturating_mul(4) {
+        if sorted_start == start {
+            return None;
+        }
+        // With `total_len >= 4 * sorted_len` the next expansion always stays
+        // inside `start..=end`; clamping to `start` only guards logic errors.
+        let next_expansion = sorted_len.saturating_mul(2).saturating_add(1);
+        let left_bound = end
+            .checked_sub(next_expansion)
+            .filter(|&bound| bound >= start)
+            .unwrap_or(start);
+        return Some(chunk(left_bound, false));
+    }
+    (sorted_start > start).then(|| chunk(start, true))
+}
+
+/// Prepares merging the chunk `arr[left_bound..sorted_start]` into the
+/// sorted suffix `arr[sorted_start..=end]`: returns the chunk's actual start
+/// and the downwave that merges it, if one is needed.
+///
+/// A natural run ending just before the suffix (a strictly descending one is
+/// reversed first) is never cut: if it reaches past `left_bound` the chunk
+/// grows to the run's start, no further than `start`. When that whole chunk
+/// is one run that joins the suffix in order, nothing needs merging.
+fn merge_chunk<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    start: usize,
+    left_bound: usize,
+    sorted_start: usize,
+    end: usize,
+    ctx: &mut Ctx<'_, T, F, C>,
+    depth: u32,
+) -> (usize, Option<Wave>) {
+    let down = |start| Wave::Down {
+        start,
+        sorted_start,
+        end,
+        depth,
+    };
+    let run = natural_run(arr, start, sorted_start - 1, ctx);
+    if run <= left_bound {
+        ctx.counter.compared();
+        if (ctx.compare)(&arr[sorted_start - 1], &arr[sorted_start]) != Ordering::Greater {
+            return (run, None);
+        }
+        return (run, Some(down(run)));
+    }
+    (left_bound, Some(down(left_bound)))
+}
+
+/// Returns the start of the natural run ending at `arr[hi]`, no lower than
+/// `lo`. A strictly descending run is reversed in place, so `arr[run..=hi]`
+/// is always ascending afterwards.
+fn natural_run<T, F: FnMut(&T, &T) -> Ordering, C: Counter>(
+    arr: &mut [T],
+    lo: usize,
+    hi: usize,
+    ctx: &mut Ctx<'_, T, F, C>,
+) -> usize {
+    let mut k = hi;
+    if k == lo {
+        return k;
+    }
+    ctx.counter.compared();
+    if (ctx.compare)(&arr[k - 1], &arr[k]) == Ordering::Greater {
+        k -= 1;
+        while k > lo && {
+            ctx.counter.compared();
+            (ctx.compare)(&arr[k - 1], &arr[k]) == Ordering::Greater
+        } {
+            k -= 1;
+        }
+        arr[k..=hi].reverse();
+    } else {
+        k -= 1;
+        while k > lo && {
+            ctx.counter.compared();
+            (ctx.compare)(&arr[k - 1], &arr[k]) != Ordering::Greater
+        } {
+            k -= 1;
+        }
+    }
+    k
+}
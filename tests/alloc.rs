@@ -0,0 +1,76 @@
+//! Checks that the in-place sorts never touch the heap, using a global
+//! allocator that counts what the current thread allocates.
+//!
+//! Not built with the `trace` feature, whose `eprintln!` output the test
+//! harness captures into a heap buffer.
+
+#![cfg(not(feature = "trace"))]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    static ALLOCATED: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.with(|a| a.set(a.get().map(|n| n + layout.size())));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Bytes allocated on this thread while running `f`.
+fn allocated_by(f: impl FnOnce()) -> usize {
+    ALLOCATED.with(|a| a.set(Some(0)));
+    f();
+    ALLOCATED.with(|a| a.replace(None)).unwrap()
+}
+
+fn assert_in_place(name: &str, input: &[i32], sort: fn(&mut [i32])) {
+    let mut v = input.to_vec();
+    assert_eq!(allocated_by(|| sort(&mut v)), 0, "{name}");
+}
+
+#[test]
+fn in_place_sorts_do_not_allocate() {
+    let input: Vec<i32> = (0..200_000i64)
+        .map(|i| (i * 2_654_435_761 % 4_294_967_291) as i32)
+        .collect();
+    assert_in_place("sort_unstable", &input, wsort::sort_unstable);
+    assert_in_place("sort_desc", &input, wsort::sort_desc);
+    assert_in_place("sort", &input, wsort::sort);
+    assert_in_place("sort_i32", &input, wsort::sort_i32);
+    assert_in_place("sort_asm", &input, wsort::sort_asm);
+    assert_in_place("partial_sort", &input, |v| wsort::partial_sort(v, 1_000));
+    // The allocator really counts
+    assert!(allocated_by(|| drop(wsort::sorted(&input))) > 0);
+}
+
+#[test]
+fn sort_bytes_does_not_allocate() {
+    let mut v: Vec<u8> = (0..100_000u32).map(|i| (i * 7919) as u8).collect();
+    assert_eq!(allocated_by(|| wsort::sort_bytes(&mut v)), 0);
+}
+
+#[test]
+fn wave_sorter_reuses_its_scratch() {
+    let mut sorter = wsort::WaveSorter::with_config(wsort::SortConfig {
+        radix_threshold: Some(1_000),
+        ..Default::default()
+    });
+    let mut v: Vec<i32> = (0..50_000i64)
+        .map(|i| (i * 2_654_435_761 % 4_294_967_291) as i32)
+        .collect();
+    sorter.sort(&mut v.clone());
+    assert_eq!(allocated_by(|| sorter.sort(&mut v)), 0);
+}
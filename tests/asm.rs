@@ -0,0 +1,360 @@
+fn lcg_i64(n: usize, mut seed: u64) -> Vec<i64> {
+    (0..n)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            seed as i64
+        })
+        .collect()
+}
+
+#[test]
+fn asm_i64_matches_std() {
+    for n in [0, 1, 2, 3, 31, 32, 33, 1_000, 100_000] {
+        let mut data = lcg_i64(n, n as u64);
+        let mut expected = data.clone();
+        expected.sort();
+        wsort::wavesort_asm_safe_i64(&mut data);
+        assert_eq!(data, expected, "n = {n}");
+    }
+}
+
+#[test]
+fn asm_i64_extremes() {
+    let mut data = vec![i64::MAX, 0, i64::MIN, -1, 1, i64::MIN, i64::MAX];
+    wsort::wavesort_asm_safe_i64(&mut data);
+    assert_eq!(data, [i64::MIN, i64::MIN, -1, 0, 1, i64::MAX, i64::MAX]);
+}
+
+#[test]
+fn asm_tiers_agree() {
+    let base: Vec<i32> = lcg_i64(200_000, 3).into_iter().map(|v| v as i32).collect();
+    let mut scalar = base.clone();
+    wsort::wavesort_asm_tier(&mut scalar, wsort::AsmTier::Scalar);
+    let mut expected = base.clone();
+    expected.sort();
+    assert_eq!(scalar, expected);
+
+    for tier in [wsort::AsmTier::Sse2, wsort::AsmTier::Avx2] {
+        if tier.is_supported() {
+            let mut vectorized = base.clone();
+            wsort::wavesort_asm_tier(&mut vectorized, tier);
+            assert_eq!(vectorized, scalar, "{tier:?}");
+        }
+    }
+
+    let mut dispatched = base;
+    wsort::wavesort_asm_safe(&mut dispatched);
+    assert_eq!(dispatched, scalar);
+}
+
+#[test]
+fn asm_variant_sorts_long_block_swaps() {
+    // `WSORT_ARCH` picks the kernel at build time; CI runs this once per
+    // variant, so check that the requested one is the one that linked.
+    if let (Some(requested), Some(built)) = (option_env!("WSORT_ARCH"), wsort::ASM_VARIANT) {
+        assert_eq!(built, requested);
+    }
+    // Two interleaved sorted runs: every upwave merge rotates long blocks,
+    // covering the unrolled loops and their scalar tails at many lengths.
+    for n in [31i32, 32, 33, 63, 64, 65, 100, 1_000, 65_537] {
+        let base: Vec<i32> = (0..n).map(|i| if i % 2 == 0 { i } else { n - i }).collect();
+        let mut expected = base.clone();
+        expected.sort();
+        for tier in [
+            wsort::AsmTier::Scalar,
+            wsort::AsmTier::Sse2,
+            wsort::AsmTier::Avx2,
+        ] {
+            if tier.is_supported() {
+                let mut v = base.clone();
+                wsort::wavesort_asm_tier(&mut v, tier);
+                assert_eq!(v, expected, "{tier:?}, n = {n}");
+            }
+        }
+    }
+}
+
+#[test]
+fn asm_tier_detection_prefers_the_widest() {
+    let detected = wsort::AsmTier::detect();
+    assert!(detected.is_supported());
+    if cfg!(target_arch = "x86_64") {
+        // SSE2 is part of x86-64, so scalar is never picked there
+        assert!(wsort::AsmTier::Sse2.is_supported());
+        let expected = if wsort::AsmTier::Avx2.is_supported() {
+            wsort::AsmTier::Avx2
+        } else {
+            wsort::AsmTier::Sse2
+        };
+        assert_eq!(detected, expected);
+    } else {
+        assert_eq!(detected, wsort::AsmTier::Scalar);
+    }
+    // Every tier the CPU supports sorts small and edge-case inputs alike
+    for tier in [
+        wsort::AsmTier::Scalar,
+        wsort::AsmTier::Sse2,
+        wsort::AsmTier::Avx2,
+    ] {
+        if !tier.is_supported() {
+            continue;
+        }
+        for n in [2, 3, 7, 8, 9, 15, 16, 17, 100] {
+            let mut v: Vec<i32> = lcg_i64(n, n as u64).into_iter().map(|x| x as i32).collect();
+            v.extend([i32::MIN, i32::MAX, 0, 0, -1]);
+            let mut expected = v.clone();
+            expected.sort();
+            wsort::wavesort_asm_tier(&mut v, tier);
+            assert_eq!(v, expected, "{tier:?}, n = {n}");
+        }
+    }
+}
+
+#[test]
+fn has_asm_reports_the_linked_backend() {
+    // CI installs an assembler on every runner and sets this, so a build
+    // that quietly fell back to the Rust stubs fails there.
+    let required = option_env!("WSORT_REQUIRE_ASM").is_some_and(|v| v == "1");
+    assert!(
+        wsort::HAS_ASM || !required,
+        "built without the assembly backend"
+    );
+    // The kernel variant is only chosen when NASM assembles the x86-64 file
+    if cfg!(target_arch = "x86_64") {
+        assert_eq!(wsort::HAS_ASM, wsort::ASM_VARIANT.is_some());
+    } else {
+        assert_eq!(wsort::ASM_VARIANT, None);
+    }
+    // Either way the entry points sort
+    let mut v = vec![3, -1, 2, i32::MIN, 0];
+    wsort::sort_asm(&mut v);
+    assert_eq!(v, [i32::MIN, -1, 0, 2, 3]);
+}
+
+#[test]
+fn asm_empty_and_single() {
+    let mut empty: [i32; 0] = [];
+    wsort::wavesort_asm_safe(&mut empty);
+    let mut one = [42];
+    wsort::wavesort_asm_safe(&mut one);
+    assert_eq!(one, [42]);
+
+    let mut empty: [i64; 0] = [];
+    wsort::wavesort_asm_safe_i64(&mut empty);
+    for tier in [wsort::AsmTier::Scalar, wsort::AsmTier::detect()] {
+        wsort::wavesort_asm_tier(&mut [], tier);
+    }
+}
+
+#[test]
+fn u32_straddles_sign_bit() {
+    let mut data: Vec<u32> = (0..5_000u32)
+        .map(|i| 0x8000_0000u32.wrapping_add(i.wrapping_mul(2_654_435_761) % 4_000) - 2_000)
+        .collect();
+    data.extend([0, u32::MAX, 0x7FFF_FFFF, 0x8000_0000]);
+    let mut expected = data.clone();
+    expected.sort();
+
+    let mut rust = data.clone();
+    wsort::sort_u32(&mut rust);
+    assert_eq!(rust, expected);
+
+    wsort::wavesort_asm_safe_u32(&mut data);
+    assert_eq!(data, expected);
+}
+
+#[test]
+fn asm_desc_matches_reversed_sort() {
+    for n in [0, 1, 2, 3, 31, 32, 33, 1_000, 100_000] {
+        let mut data: Vec<i32> = lcg_i64(n, n as u64 + 7)
+            .into_iter()
+            .map(|x| (x >> 40) as i32)
+            .collect();
+        data.extend([i32::MIN, i32::MAX, 0, 0]);
+        let mut expected = data.clone();
+        expected.sort();
+        expected.reverse();
+        wsort::wavesort_asm_safe_desc(&mut data);
+        assert_eq!(data, expected, "n = {n}");
+    }
+}
+
+#[test]
+fn asm_dir_matches_std_both_ways() {
+    // The wrapper takes the detected tier for ascending sorts, so the C
+    // entry point is checked separately in both directions.
+    unsafe extern "C" {
+        fn wave_sort_cmp(arr: *mut i32, len: usize, descending: std::ffi::c_int);
+    }
+    for n in [0, 1, 2, 3, 5, 31, 32, 33, 1_000, 100_000] {
+        // Full-range values, then only 8 distinct ones
+        for shift in [32, 61] {
+            let mut data: Vec<i32> = lcg_i64(n, n as u64 + 11)
+                .into_iter()
+                .map(|x| (x >> shift) as i32)
+                .collect();
+            data.extend([i32::MIN, i32::MAX, -1, 0, 0]);
+            let mut ascending = data.clone();
+            ascending.sort();
+            let mut descending = ascending.clone();
+            descending.reverse();
+
+            let mut up = data.clone();
+            wsort::wavesort_asm_safe_dir(&mut up, false);
+            assert_eq!(up, ascending, "n = {n}, shift = {shift}, ascending");
+            let mut up = data.clone();
+            unsafe { wave_sort_cmp(up.as_mut_ptr(), up.len(), 0) };
+            assert_eq!(up, ascending, "n = {n}, shift = {shift}, wave_sort_cmp 0");
+
+            let mut down = data.clone();
+            unsafe { wave_sort_cmp(down.as_mut_ptr(), down.len(), 1) };
+            assert_eq!(
+                down, descending,
+                "n = {n}, shift = {shift}, wave_sort_cmp 1"
+            );
+            wsort::wavesort_asm_safe_dir(&mut data, true);
+            assert_eq!(data, descending, "n = {n}, shift = {shift}, descending");
+        }
+    }
+}
+
+// Links against the exported C names directly, so a symbol that is spelled
+// wrong for the object format (e.g. missing the Mach-O underscore) fails here.
+#[test]
+fn c_symbols_link() {
+    unsafe extern "C" {
+        fn wave_sort(arr: *mut i32, len: usize);
+        fn wave_sort_i64(arr: *mut i64, len: usize);
+        fn wave_sort_u32(arr: *mut u32, len: usize);
+        fn wave_sort_cmp(arr: *mut i32, len: usize, descending: std::ffi::c_int);
+        fn wave_sort_i16(arr: *mut i16, len: usize);
+    }
+    let mut a = [3, -1, 2];
+    let mut b = [3i64, -1, 2];
+    let mut c = [3u32, 1, 2];
+    unsafe {
+        wave_sort(a.as_mut_ptr(), a.len());
+        wave_sort_i64(b.as_mut_ptr(), b.len());
+        wave_sort_u32(c.as_mut_ptr(), c.len());
+    }
+    let mut d = [3, -1, 2];
+    unsafe { wave_sort_cmp(d.as_mut_ptr(), d.len(), 1) };
+    assert_eq!(d, [3, 2, -1]);
+    unsafe { wave_sort_cmp(d.as_mut_ptr(), d.len(), 0) };
+    assert_eq!(d, [-1, 2, 3]);
+    let mut e = [3i16, -1, 2];
+    unsafe { wave_sort_i16(e.as_mut_ptr(), e.len()) };
+    assert_eq!(e, [-1, 2, 3]);
+    assert_eq!(a, [-1, 2, 3]);
+    assert_eq!(b, [-1, 2, 3]);
+    assert_eq!(c, [1, 2, 3]);
+    wsort::wavesort_asm_safe(&mut a);
+    assert_eq!(a, [-1, 2, 3]);
+}
+
+#[test]
+fn try_wavesort_asm_sorts_slices() {
+    let mut v = vec![5, -3, 9, 0, -3];
+    assert_eq!(wsort::try_wavesort_asm(&mut v), Ok(()));
+    assert_eq!(v, [-3, -3, 0, 5, 9]);
+    assert_eq!(wsort::try_wavesort_asm(&mut []), Ok(()));
+}
+
+#[test]
+fn try_wavesort_asm_raw_rejects_bad_input() {
+    use wsort::SortError;
+
+    let err = unsafe { wsort::try_wavesort_asm_raw(std::ptr::null_mut(), 4) };
+    assert_eq!(err, Err(SortError::NullPointer));
+
+    let mut bytes = [0u8; 16];
+    let base = bytes.as_mut_ptr();
+    let offset = if (base as usize).is_multiple_of(4) {
+        1
+    } else {
+        0
+    };
+    let ptr = base.wrapping_add(offset) as *mut i32;
+    let err = unsafe { wsort::try_wavesort_asm_raw(ptr, 2) };
+    assert_eq!(
+        err,
+        Err(SortError::Misaligned {
+            addr: ptr as usize,
+            align: 4
+        })
+    );
+
+    let mut v = [0i32; 1];
+    let err = unsafe { wsort::try_wavesort_asm_raw(v.as_mut_ptr(), usize::MAX) };
+    assert!(matches!(
+        err,
+        Err(SortError::TooLong {
+            len: usize::MAX,
+            ..
+        })
+    ));
+    assert_eq!(SortError::NullPointer.to_string(), "null data pointer");
+}
+
+#[test]
+fn asm_sort_dispatches_on_element_type() {
+    let mut ints: Vec<i32> = (0..10_000).map(|i| (i * 7919) % 10_007 - 5_000).collect();
+    let mut expected = ints.clone();
+    expected.sort();
+    wsort::asm_sort(&mut ints);
+    assert_eq!(ints, expected);
+
+    let mut wide = lcg_i64(1_000, 3);
+    let mut expected = wide.clone();
+    expected.sort();
+    wsort::asm_sort(&mut wide);
+    assert_eq!(wide, expected);
+
+    let mut unsigned = [u32::MAX, 0, 1 << 31, 7];
+    wsort::asm_sort(&mut unsigned);
+    assert_eq!(unsigned, [0, 7, 1 << 31, u32::MAX]);
+
+    let mut empty: [i32; 0] = [];
+    wsort::asm_sort(&mut empty);
+}
+
+#[test]
+fn aligned_vecs_pass_the_alignment_check() {
+    // Debug builds assert alignment in every wrapper; a `Vec` always passes
+    let mut ints: Vec<i32> = (0..1_000).rev().collect();
+    assert_eq!(ints.as_ptr() as usize % align_of::<i32>(), 0);
+    wsort::wavesort_asm_safe(&mut ints);
+    wsort::wavesort_asm_safe_desc(&mut ints);
+    wsort::wavesort_asm_tier(&mut ints, wsort::AsmTier::Scalar);
+    wsort::asm_sort(&mut ints[1..]);
+    assert!(wsort::is_sorted(&ints));
+
+    let mut wide = lcg_i64(1_000, 9);
+    wsort::wavesort_asm_safe_i64(&mut wide[1..]);
+    let mut unsigned: Vec<u32> = (0..1_000).rev().collect();
+    wsort::wavesort_asm_safe_u32(&mut unsigned[3..]);
+    assert!(wsort::is_sorted(&unsigned[3..]));
+}
+
+#[test]
+fn asm_i16_matches_std() {
+    for n in [0, 1, 2, 3, 31, 32, 33, 1_000, 100_000] {
+        let mut samples: Vec<i16> = lcg_i64(n, n as u64 + 5)
+            .into_iter()
+            .map(|x| (x >> 48) as i16)
+            .collect();
+        samples.extend([i16::MIN, i16::MAX, 0, -1, i16::MIN, i16::MAX]);
+        let mut expected = samples.clone();
+        expected.sort();
+        let mut generic = samples.clone();
+        wsort::wavesort_asm_safe_i16(&mut samples);
+        assert_eq!(samples, expected, "n = {n}");
+        wsort::sort_unstable(&mut generic);
+        assert_eq!(generic, expected, "n = {n}");
+    }
+    let mut pcm = [i16::MAX, 0, i16::MIN, -1, 1];
+    wsort::asm_sort(&mut pcm);
+    assert_eq!(pcm, [i16::MIN, -1, 0, 1, i16::MAX]);
+}
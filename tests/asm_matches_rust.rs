@@ -0,0 +1,30 @@
+//! The assembly and pure Rust backends must agree on every benchmark input.
+
+#[allow(dead_code)]
+#[path = "../src/datagen.rs"]
+mod datagen;
+
+use datagen::Dist;
+
+const DISTS: [Dist; 6] = [
+    Dist::Random,
+    Dist::Sorted,
+    Dist::Reversed,
+    Dist::NearlySorted,
+    Dist::AllEqual,
+    Dist::Sawtooth,
+];
+
+#[test]
+fn asm_matches_rust() {
+    for dist in DISTS {
+        for n in [0, 1, 2, 31, 32, 33, 1_000, 65_537, 300_000] {
+            let mut rust = dist.generate(n, n as u64 + 1);
+            let mut asm = rust.clone();
+            wsort::sort_unstable(&mut rust);
+            wsort::sort_asm(&mut asm);
+            assert!(wsort::is_sorted(&rust), "{} n = {n}", dist.name());
+            assert_eq!(asm, rust, "{} n = {n}", dist.name());
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_wsort-cli"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn sorts_stdin() {
+    let out = run(&[], "3 1 2");
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "1\n2\n3\n");
+}
+
+#[test]
+fn flags() {
+    for args in [&["--desc"][..], &["--desc", "--asm", "--check"]] {
+        let out = run(args, "3\n-1 2\n\n10");
+        assert!(out.status.success(), "{args:?}");
+        assert_eq!(String::from_utf8(out.stdout).unwrap(), "10\n3\n2\n-1\n");
+    }
+    let out = run(&["--asm", "--check"], "5 4 3");
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "3\n4\n5\n");
+}
+
+#[test]
+fn rejects_bad_input() {
+    assert!(!run(&[], "1 two 3").status.success());
+    assert_eq!(run(&["--bogus"], "").status.code(), Some(2));
+}
+
+#[test]
+fn benchmark_smoke_test_exits_zero() {
+    let out = Command::new(env!("CARGO_BIN_EXE_wsort"))
+        .args(["--n", "2000", "--dist", "sawtooth", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert!(out.stderr.is_empty());
+    assert!(
+        String::from_utf8(out.stdout)
+            .unwrap()
+            .contains("\"copy_secs\":")
+    );
+}
+
+#[test]
+fn benchmark_repeat_reports_min_median_max() {
+    let out = Command::new(env!("CARGO_BIN_EXE_wsort"))
+        .args(["--n", "2000", "--repeat", "3", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.contains("\"repeat\":3"), "{stdout}");
+    for backend in ["rust", "asm", "std", "std-unstable"] {
+        assert!(
+            stdout.contains(&format!("\"{backend}\":{{\"min_secs\":")),
+            "{stdout}"
+        );
+    }
+
+    for bad in [&["--repeat", "0"][..], &["--repeat", "2", "--sweep"]] {
+        let out = Command::new(env!("CARGO_BIN_EXE_wsort"))
+            .args(bad)
+            .output()
+            .unwrap();
+        assert_eq!(out.status.code(), Some(2), "{bad:?}");
+    }
+}
+
+#[test]
+fn benchmark_sweep_rejects_json() {
+    // The sweep only prints CSV, so asking for JSON is an error rather than
+    // silently getting CSV
+    let out = Command::new(env!("CARGO_BIN_EXE_wsort"))
+        .args(["--sweep", "--format", "json"])
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(2));
+    assert!(out.stdout.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn benchmark_json_escapes_the_input_path() {
+    let input = std::env::temp_dir().join(format!(
+        "wsort \"quoted\" back\\slash \u{1b} {}.bin",
+        std::process::id()
+    ));
+    std::fs::write(&input, [2, 0, 0, 0, 1, 0, 0, 0]).unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_wsort"))
+        .arg("--input")
+        .arg(&input)
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_file(&input);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(
+        stdout.contains(r#"wsort \"quoted\" back\\slash \u001b "#),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn benchmark_sorts_a_binary_file() {
+    let dir = std::env::temp_dir();
+    let input = dir.join(format!("wsort-input-{}.bin", std::process::id()));
+    let output = dir.join(format!("wsort-output-{}.bin", std::process::id()));
+    let values: Vec<i32> = (0..10_000i64)
+        .map(|i| (i * 2_654_435_761 % 4_294_967_291 - 2_147_483_645) as i32)
+        .collect();
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    std::fs::write(&input, bytes).unwrap();
+
+    for backend in ["rust", "asm", "std", "std-unstable"] {
+        let out = Command::new(env!("CARGO_BIN_EXE_wsort"))
+            .arg("--input")
+            .arg(&input)
+            .arg("--output")
+            .arg(&output)
+            .args(["--backend", backend])
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "{backend}: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        let sorted: Vec<i32> = std::fs::read(&output)
+            .unwrap()
+            .chunks_exact(4)
+            .map(|b| i32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        let mut expected = values.clone();
+        expected.sort();
+        assert_eq!(sorted, expected, "{backend}");
+    }
+
+    // A trailing partial integer is rejected rather than dropped
+    std::fs::write(&input, [1, 0, 0, 0, 2]).unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_wsort"))
+        .arg("--input")
+        .arg(&input)
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(1));
+
+    let _ = std::fs::remove_file(&input);
+    let _ = std::fs::remove_file(&output);
+}
@@ -0,0 +1,93 @@
+//! Sorts `i32`s in place inside memory mappings, the out-of-core use case:
+//! the sort writes straight through the mapping, so a mapped file holds the
+//! sorted values afterwards with no copy in between.
+//!
+//! The mappings come from memmap2. Run it with `cargo test --features mmap`.
+
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use memmap2::MmapMut;
+
+type SortFn = fn(&mut [i32]);
+
+const SORTS: [(&str, SortFn); 3] = [
+    ("sort", wsort::sort),
+    ("sort_unstable", wsort::sort_unstable),
+    ("wavesort_asm_safe", wsort::wavesort_asm_safe),
+];
+
+fn as_i32s(map: &mut MmapMut) -> &mut [i32] {
+    // Mappings are page aligned, so nothing is left over before the `i32`s
+    let (head, values, tail) = unsafe { map.align_to_mut::<i32>() };
+    assert!(head.is_empty() && tail.is_empty());
+    values
+}
+
+fn temp_file(name: &str, values: &[i32]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("wsort-{name}-{}.bin", std::process::id()));
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_ne_bytes()).collect();
+    fs::write(&path, bytes).unwrap();
+    path
+}
+
+fn read_file(path: &Path) -> Vec<i32> {
+    fs::read(path)
+        .unwrap()
+        .chunks_exact(4)
+        .map(|b| i32::from_ne_bytes(b.try_into().unwrap()))
+        .collect()
+}
+
+fn values(n: usize) -> Vec<i32> {
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut v: Vec<i32> = (0..n)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 32) as i32
+        })
+        .collect();
+    v.extend([i32::MIN, i32::MAX, 0, 0]);
+    v
+}
+
+#[test]
+fn sorts_an_anonymous_mapping_in_place() {
+    let data = values(1 << 18);
+    let mut expected = data.clone();
+    expected.sort();
+
+    for (name, sort) in SORTS {
+        let mut map = MmapMut::map_anon(data.len() * 4).unwrap();
+        as_i32s(&mut map).copy_from_slice(&data);
+        sort(as_i32s(&mut map));
+        assert_eq!(as_i32s(&mut map), &expected[..], "{name}");
+    }
+}
+
+#[test]
+fn sorts_a_memory_mapped_file_in_place() {
+    let data = values(1 << 18);
+    let mut expected = data.clone();
+    expected.sort();
+
+    for (name, sort) in SORTS {
+        let path = temp_file(name, &data);
+        {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&path)
+                .unwrap();
+            let mut map = unsafe { MmapMut::map_mut(&file) }.unwrap();
+            sort(as_i32s(&mut map));
+            assert!(wsort::is_sorted(as_i32s(&mut map)), "{name}");
+            map.flush().unwrap();
+        }
+        // Unmapped: the file itself now holds the sorted values
+        assert_eq!(read_file(&path), expected, "{name}");
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,25 @@
+//! Exercises the `core`-only surface from a `#![no_std]` crate.
+//!
+//! Build the library itself without `std` with
+//! `cargo build --lib --no-default-features`.
+
+#![no_std]
+
+#[test]
+fn sorts_without_std() {
+    let mut v = [5u8, 3, 9, 1, 1, 0];
+    wsort::sort_unstable(&mut v);
+    assert!(wsort::is_sorted(&v));
+    assert_eq!(v, [0, 1, 1, 3, 5, 9]);
+
+    let mut words = ["b", "c", "a"];
+    wsort::sort_desc(&mut words);
+    assert_eq!(words, ["c", "b", "a"]);
+}
+
+#[test]
+fn sort_bytes_without_std() {
+    let mut v = [200u8, 0, 17, 255, 17];
+    wsort::sort_bytes(&mut v);
+    assert_eq!(v, [0, 17, 17, 200, 255]);
+}
@@ -0,0 +1,95 @@
+//! A comparison that panics part-way must leave the slice a permutation of
+//! its input: every element exactly once, none duplicated or leaked.
+
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::panic::{self, AssertUnwindSafe};
+
+thread_local! {
+    /// Comparisons left before the next one panics.
+    static BUDGET: Cell<usize> = const { Cell::new(usize::MAX) };
+    static DROPS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Owns a heap allocation, so a double drop would be a double free, and
+/// counts its drops. Comparing spends one unit of `BUDGET`.
+struct Tracked(Box<i32>);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        DROPS.with(|d| d.set(d.get() + 1));
+    }
+}
+
+impl Ord for Tracked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let left = BUDGET.with(|b| b.replace(b.get().saturating_sub(1)));
+        if left == 0 {
+            panic!("comparison budget exhausted");
+        }
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialOrd for Tracked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Tracked {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Tracked {}
+
+/// Sorts with comparisons that panic after `budget` calls, then checks that
+/// the slice still holds every original value and drops each exactly once.
+fn check(name: &str, sort: fn(&mut [Tracked])) {
+    for len in [2, 20, 33, 1_000, 10_000] {
+        for budget in [0, 1, 17, 500, 20_000] {
+            let mut v: Vec<Tracked> = (0..len as i64)
+                .map(|i| Tracked(Box::new((i * 2_654_435_761 % 1_000_003) as i32)))
+                .collect();
+            let mut expected: Vec<i32> = v.iter().map(|t| *t.0).collect();
+            expected.sort();
+
+            BUDGET.with(|b| b.set(budget));
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| sort(&mut v)));
+            BUDGET.with(|b| b.set(usize::MAX));
+
+            let mut values: Vec<i32> = v.iter().map(|t| *t.0).collect();
+            values.sort();
+            assert_eq!(values, expected, "{name} len = {len} budget = {budget}");
+
+            DROPS.with(|d| d.set(0));
+            drop(v);
+            assert_eq!(DROPS.with(Cell::get), len, "{name}");
+        }
+    }
+}
+
+#[test]
+fn panicking_comparison_keeps_a_permutation() {
+    // Keep the expected panics out of the test output
+    panic::set_hook(Box::new(|_| {}));
+    check("sort_unstable", wsort::sort_unstable);
+    check("sort_desc", wsort::sort_desc);
+    check("sort_by", |v| wsort::sort_by(v, |a, b| b.cmp(a)));
+    check("long leaves", |v| {
+        let cfg = wsort::SortConfig {
+            insertion_threshold: 64,
+            ..Default::default()
+        };
+        wsort::sort_with_config(v, &cfg);
+    });
+    check("sort", wsort::sort);
+    check("merge_sort", wsort::merge_sort);
+    check("partial_sort", |v| wsort::partial_sort(v, 10));
+    check("partition", |v| {
+        wsort::partition(v, 0);
+    });
+    let _ = panic::take_hook();
+}
@@ -0,0 +1,242 @@
+//! Property tests: for generated inputs, every sort must agree with
+//! `slice::sort`. Matching a sorted clone also proves the output is a
+//! permutation of the input. proptest shrinks a failing input before
+//! reporting it.
+
+use std::ops::RangeInclusive;
+
+use proptest::collection::{SizeRange, vec};
+use proptest::prelude::*;
+use proptest::sample::{Index, select};
+use wsort::Partition;
+
+/// `len` values in one of several shapes.
+fn shaped(len: RangeInclusive<usize>) -> impl Strategy<Value = Vec<i32>> {
+    let few = {
+        let len = len.clone();
+        (1..=16i32).prop_flat_map(move |distinct| vec(0..distinct, len.clone()))
+    };
+    let swaps = vec((any::<Index>(), any::<Index>()), 0..4);
+    prop_oneof![
+        // Few distinct values, many duplicates
+        few,
+        // Extremes mixed with small values
+        vec(select(&[i32::MIN, i32::MAX, 0, -1, 1][..]), len.clone()),
+        vec(any::<i32>(), len.clone()),
+        // Presorted either way, then perturbed a little
+        (vec(any::<i32>(), len), any::<bool>(), swaps).prop_map(|(mut v, descending, swaps)| {
+            v.sort();
+            if descending {
+                v.reverse();
+            }
+            if !v.is_empty() {
+                for (i, j) in swaps {
+                    let (i, j) = (i.index(v.len()), j.index(v.len()));
+                    v.swap(i, j);
+                }
+            }
+            v
+        }),
+    ]
+}
+
+/// Lengths around the insertion sort threshold (32) plus a few larger ones.
+fn input() -> impl Strategy<Value = Vec<i32>> {
+    prop_oneof![
+        shaped(0..=3),
+        shaped(16..=64),
+        shaped(65..=1_000),
+        shaped(1_001..=20_000),
+    ]
+}
+
+/// `len` values below `distinct`, spread over the whole `i32` range when
+/// `distinct` is `1 << 32`.
+fn below(distinct: u64, len: impl Into<SizeRange>) -> impl Strategy<Value = Vec<i32>> {
+    vec((0..distinct).prop_map(|x| x as i32), len)
+}
+
+/// Sorts a copy of `input` with `sort` and compares it with `slice::sort`.
+fn check(input: &[i32], sort: impl FnOnce(&mut [i32])) -> Result<(), TestCaseError> {
+    let mut expected = input.to_vec();
+    expected.sort();
+    let mut actual = input.to_vec();
+    sort(&mut actual);
+    prop_assert_eq!(actual, expected);
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn rust_sort_matches_std(v in input()) {
+        check(&v, wsort::sort_unstable)?;
+    }
+
+    #[test]
+    fn asm_sort_matches_std(v in input()) {
+        check(&v, wsort::wavesort_asm_safe)?;
+    }
+
+    #[test]
+    fn small_thresholds_match_std(v in input(), threshold in select(&[2, 3, 9][..])) {
+        // Tiny leaves mean many more partitions and waves per input, and debug
+        // builds assert the index invariants of every one of them.
+        check(&v, |v| with_threshold(v, threshold))?;
+    }
+
+    #[test]
+    fn long_leaves_match_std(v in input(), threshold in select(&[16, 64, 1_000][..])) {
+        // Leaves past the sorting networks go through the binary insertion sort;
+        // large thresholds make its search and bulk shifts cover long distances.
+        check(&v, |v| with_threshold(v, threshold))?;
+    }
+
+    #[test]
+    fn sort_desc_matches_std(v in input()) {
+        check(&v, |v| {
+            wsort::sort_desc(v);
+            v.reverse();
+        })?;
+    }
+
+    #[test]
+    fn every_partition_matches_std(
+        v in input(),
+        partition in select(&[Partition::Auto, Partition::TwoWay, Partition::ThreeWay][..]),
+    ) {
+        check(&v, |v| with_partition(v, partition))?;
+    }
+
+    #[test]
+    fn downwave_edge_splits_match_recursion(
+        v in vec(0..1_000i32, 3..=1_000),
+        split in any::<Index>(),
+        offset in select(&[-1_000, 0, 1_000][..]),
+        threshold in select(&[2, 3, 32][..]),
+    ) {
+        // A sorted tail lying wholly above or below the unsorted prefix makes the
+        // pivot land at either end of the sorted block, so one side of every
+        // downwave split is empty. The positions paired with the values show
+        // that the iterative driver moves equal keys exactly like the recursion.
+        let mut v = v;
+        let split = 1 + split.index(v.len() - 1);
+        v[split..].sort();
+        v[split..].iter_mut().for_each(|x| *x += offset);
+        check(&v, |v| with_threshold(v, threshold))?;
+
+        let cfg = wsort::SortConfig {
+            insertion_threshold: threshold,
+            ..Default::default()
+        };
+        let input: Vec<Keyed> = v.into_iter().zip(0..).map(Keyed).collect();
+        let mut iterative = input.clone();
+        wsort::sort_with_config(&mut iterative, &cfg);
+        let mut recursive = input;
+        wsort::sort_recursive_with_config(&mut recursive, &cfg);
+        let pairs = |v: &[Keyed]| v.iter().map(|k| k.0).collect::<Vec<_>>();
+        prop_assert_eq!(pairs(&iterative), pairs(&recursive));
+    }
+
+    #[test]
+    fn work_stack_matches_recursion(v in input()) {
+        // Pair each value with its input position; only the value takes part in
+        // comparisons, so the positions show exactly how equal keys were moved.
+        let input: Vec<(i32, usize)> = v.into_iter().zip(0..).collect();
+        let mut iterative = input.clone();
+        wsort::sort_by_key(&mut iterative, |&(v, _)| v);
+        let mut recursive: Vec<Keyed> = input.into_iter().map(Keyed).collect();
+        wsort::sort_recursive(&mut recursive);
+        let recursive: Vec<(i32, usize)> = recursive.into_iter().map(|k| k.0).collect();
+        prop_assert_eq!(iterative, recursive);
+    }
+}
+
+proptest! {
+    // Long inputs; fewer cases keep the debug build quick.
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn many_duplicates_match_std(
+        v in select(&[1, 2, 3, 7, 100][..]).prop_flat_map(|d| below(d, 500..=50_000)),
+    ) {
+        // Long runs of equal keys send `downwave` through the three-way partition
+        check(&v, wsort::sort_unstable)?;
+        check(&v, |v| {
+            wsort::sort_desc(v);
+            v.reverse();
+        })?;
+    }
+
+    #[test]
+    fn sampled_partitions_match_std(
+        v in select(&[1, 2, 100, 10_000, 1 << 32][..]).prop_flat_map(|d| {
+            prop_oneof![below(d, 4_000..=4_200), below(d, 4_201..=50_000)]
+        }),
+    ) {
+        // `Auto` samples only slices of at least 4096 keys, and picks two-way
+        // for the high-cardinality inputs and three-way for the others.
+        for partition in [Partition::Auto, Partition::TwoWay, Partition::ThreeWay] {
+            check(&v, |v| with_partition(v, partition))?;
+        }
+    }
+}
+
+fn with_threshold(v: &mut [i32], insertion_threshold: usize) {
+    let cfg = wsort::SortConfig {
+        insertion_threshold,
+        ..Default::default()
+    };
+    wsort::sort_with_config(v, &cfg);
+}
+
+fn with_partition(v: &mut [i32], partition: Partition) {
+    let cfg = wsort::SortConfig {
+        partition,
+        ..Default::default()
+    };
+    wsort::sort_with_config(v, &cfg);
+}
+
+#[test]
+fn sorts_on_a_small_stack() {
+    let input: Vec<i32> = (0..1_000_000i64)
+        .map(|i| ((i * 7919) % 1_000_003) as i32)
+        .collect();
+    let mut expected = input.clone();
+    expected.sort();
+    let sorted = std::thread::Builder::new()
+        .stack_size(32 * 1024)
+        .spawn(move || {
+            let mut v = input;
+            wsort::sort_unstable(&mut v);
+            v
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(sorted, expected);
+}
+
+/// `(value, position)` ordered by value alone.
+#[derive(Clone, Copy)]
+struct Keyed((i32, usize));
+
+impl PartialEq for Keyed {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.0 == other.0.0
+    }
+}
+
+impl Eq for Keyed {}
+
+impl PartialOrd for Keyed {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Keyed {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.0.cmp(&other.0.0)
+    }
+}
@@ -0,0 +1,128 @@
+//! `sort_parallel` on the rayon thread pool.
+//!
+//! Run with `cargo test --features rayon --test rayon`.
+
+use std::cmp::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+
+/// Sort key with its input position, ordered by `key` alone.
+#[derive(Clone, Copy, Debug)]
+struct Record {
+    key: u32,
+    index: usize,
+}
+
+impl PartialEq for Record {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Record {}
+
+impl PartialOrd for Record {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Record {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+fn records(n: usize, distinct: u32) -> Vec<Record> {
+    (0..n)
+        .map(|index| Record {
+            key: (index as u32).wrapping_mul(2_654_435_761) % distinct,
+            index,
+        })
+        .collect()
+}
+
+#[test]
+fn rayon_sort_matches_sequential_exactly() {
+    // The joins only change which thread runs each side of a split, not what
+    // it does, so even equal keys end up where the sequential sort puts them.
+    for (n, distinct) in [(1_000, 1_000), (200_001, 1_000_003), (300_000, 7)] {
+        let base = records(n, distinct);
+        for max_threads in [0, 2, 4] {
+            let cfg = wsort::SortConfig {
+                parallel_threshold: 1_000,
+                max_threads,
+                ..Default::default()
+            };
+            let mut sequential = base.clone();
+            wsort::sort_with_config(&mut sequential, &cfg);
+            let mut parallel = base.clone();
+            wsort::sort_parallel_with_config(&mut parallel, &cfg);
+            let indices = |v: &[Record]| v.iter().map(|r| r.index).collect::<Vec<_>>();
+            assert_eq!(
+                indices(&parallel),
+                indices(&sequential),
+                "n = {n}, distinct = {distinct}, max_threads = {max_threads}"
+            );
+        }
+    }
+
+    let base: Vec<i32> = (0..100_000)
+        .map(|i| (i * 7919) % 100_003 - 50_000)
+        .collect();
+    let mut expected = base.clone();
+    expected.sort_by(|a, b| b.cmp(a));
+    let mut v = base;
+    wsort::WaveSort::new()
+        .descending(true)
+        .parallel(true)
+        .run(&mut v);
+    assert_eq!(v, expected);
+}
+
+#[test]
+fn rayon_sort_runs_on_the_pool_above_the_threshold() {
+    static ON_POOL: AtomicBool = AtomicBool::new(false);
+    static OFF_POOL: AtomicBool = AtomicBool::new(false);
+
+    /// Records which kind of thread compares it.
+    #[derive(PartialEq, Eq)]
+    struct Tracked(i32);
+
+    impl PartialOrd for Tracked {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Tracked {
+        fn cmp(&self, other: &Self) -> Ordering {
+            match rayon::current_thread_index() {
+                Some(_) => ON_POOL.store(true, AtomicOrdering::Relaxed),
+                None => OFF_POOL.store(true, AtomicOrdering::Relaxed),
+            }
+            self.0.cmp(&other.0)
+        }
+    }
+
+    let cfg = wsort::SortConfig {
+        parallel_threshold: 1_000,
+        max_threads: 4,
+        ..Default::default()
+    };
+    let values = |n: i32| (0..n).map(|i| Tracked((i * 7919) % 20_011)).collect();
+
+    // Too short for two sides of 1000: sorted on the calling thread
+    let mut v: Vec<Tracked> = values(1_999);
+    wsort::sort_parallel_with_config(&mut v, &cfg);
+    assert!(OFF_POOL.load(AtomicOrdering::Relaxed));
+    assert!(!ON_POOL.load(AtomicOrdering::Relaxed));
+    assert!(wsort::is_sorted(&v));
+
+    // Checked before `is_sorted`, which compares on this thread
+    OFF_POOL.store(false, AtomicOrdering::Relaxed);
+    let mut v: Vec<Tracked> = values(20_000);
+    wsort::sort_parallel_with_config(&mut v, &cfg);
+    assert!(ON_POOL.load(AtomicOrdering::Relaxed));
+    assert!(!OFF_POOL.load(AtomicOrdering::Relaxed));
+    assert!(wsort::is_sorted(&v));
+}
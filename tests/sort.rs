@@ -0,0 +1,1192 @@
+fn lcg_strings(n: usize) -> Vec<String> {
+    let mut seed: u64 = 7;
+    (0..n)
+        .map(|_| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            format!("s{}", (seed / 65536) % 1000)
+        })
+        .collect()
+}
+
+#[test]
+fn sorts_strings() {
+    for n in [0, 1, 2, 31, 32, 33, 100, 5_000] {
+        let mut v = lcg_strings(n);
+        let mut expected = v.clone();
+        expected.sort();
+        wsort::sort_unstable(&mut v);
+        assert_eq!(v, expected, "n = {n}");
+    }
+}
+
+#[test]
+fn sorts_rows_lexicographically() {
+    // Heap-owning elements of varying length, with shared prefixes and
+    // duplicates: any element read out by value and written back twice
+    // would show up as a double free or a mismatch here.
+    let mut seed: u64 = 11;
+    for n in [0, 1, 2, 31, 32, 33, 100, 5_000] {
+        let rows: Vec<Vec<i32>> = (0..n)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                let len = (seed >> 60) as usize;
+                (0..len)
+                    .map(|k| ((seed >> (k * 4)) % 3) as i32 - 1)
+                    .collect()
+            })
+            .collect();
+        let mut expected = rows.clone();
+        expected.sort();
+        let mut v = rows.clone();
+        wsort::sort_unstable(&mut v);
+        assert_eq!(v, expected, "sort_unstable, n = {n}");
+        let mut v = rows.clone();
+        wsort::sort(&mut v);
+        assert_eq!(v, expected, "sort, n = {n}");
+        let mut v = rows;
+        wsort::sort_desc(&mut v);
+        v.reverse();
+        assert_eq!(v, expected, "sort_desc, n = {n}");
+    }
+}
+
+#[test]
+fn sort_by_descending() {
+    let mut v: Vec<i32> = (0..1_000).map(|i| (i * 7919) % 1_000).collect();
+    wsort::sort_by(&mut v, |a, b| b.cmp(a));
+    let expected: Vec<i32> = (0..1_000).rev().collect();
+    assert_eq!(v, expected);
+}
+
+#[test]
+fn sort_by_key_first_element() {
+    let mut v = vec![(3, "c"), (1, "a"), (2, "b"), (0, "z")];
+    wsort::sort_by_key(&mut v, |&(k, _)| k);
+    assert_eq!(v, [(0, "z"), (1, "a"), (2, "b"), (3, "c")]);
+}
+
+#[test]
+fn sort_desc_small_and_large() {
+    let mut v = [3, 1, 2];
+    wsort::sort_desc(&mut v);
+    assert_eq!(v, [3, 2, 1]);
+
+    let mut v: Vec<u64> = (0..10_000).map(|i| (i * 7919) % 10_000).collect();
+    wsort::sort_desc(&mut v);
+    assert!(v.windows(2).all(|w| w[0] >= w[1]));
+}
+
+#[test]
+fn sort_f64_total_order() {
+    let mut v = [
+        f64::NAN,
+        1.5,
+        0.0,
+        f64::INFINITY,
+        -0.0,
+        -f64::NAN,
+        f64::NEG_INFINITY,
+        -2.0,
+    ];
+    wsort::sort_f64(&mut v);
+    assert!(v[0].is_nan() && v[0].is_sign_negative());
+    assert_eq!(v[1], f64::NEG_INFINITY);
+    assert_eq!(v[2], -2.0);
+    assert!(v[3] == 0.0 && v[3].is_sign_negative());
+    assert!(v[4] == 0.0 && v[4].is_sign_positive());
+    assert_eq!(v[5], 1.5);
+    assert_eq!(v[6], f64::INFINITY);
+    assert!(v[7].is_nan() && v[7].is_sign_positive());
+}
+
+#[test]
+fn sort_f32_groups_nans() {
+    let mut v = [f32::NAN, 3.0, f32::NAN, -1.0, f32::INFINITY];
+    wsort::sort_f32(&mut v);
+    assert_eq!(&v[..3], &[-1.0, 3.0, f32::INFINITY]);
+    assert!(v[3].is_nan() && v[4].is_nan());
+}
+
+#[test]
+fn deep_recursion_shapes() {
+    // Nearly presorted, nearly reversed, organ-pipe and nearly constant inputs
+    // drive the deepest upwave/downwave nesting. The single displaced element
+    // keeps them past the sorted / reversed pre-scan.
+    let n = 1 << 16;
+    let with_last = |mut v: Vec<i32>, last: i32| {
+        v.push(last);
+        v
+    };
+    let shapes: Vec<Vec<i32>> = vec![
+        with_last((1..n).collect(), 0),
+        with_last((1..n).rev().collect(), n),
+        (0..n).map(|i| i.min(n - i)).collect(),
+        with_last(vec![7; n as usize], 6),
+    ];
+    for mut v in shapes {
+        let mut expected = v.clone();
+        expected.sort();
+        wsort::sort_unstable(&mut v);
+        assert_eq!(v, expected);
+    }
+}
+
+#[test]
+fn sort_with_config_thresholds() {
+    let base: Vec<i32> = (0..20_000).map(|i| (i * 7919) % 4_099).collect();
+    let mut expected = base.clone();
+    expected.sort();
+    for insertion_threshold in [0, 1, 2, 8, 16, 32, 64, 200] {
+        let mut v = base.clone();
+        wsort::sort_with_config(
+            &mut v,
+            &wsort::SortConfig {
+                insertion_threshold,
+                ..Default::default()
+            },
+        );
+        assert_eq!(v, expected, "threshold = {insertion_threshold}");
+    }
+}
+
+#[test]
+fn sort_parallel_matches_sequential() {
+    for n in [10, 1_000, 200_001] {
+        let base: Vec<i64> = (0..n as i64)
+            .map(|i| (i * 2_654_435_761) % 1_000_003)
+            .collect();
+        let mut sequential = base.clone();
+        wsort::sort_unstable(&mut sequential);
+        let mut parallel = base;
+        wsort::sort_parallel(&mut parallel);
+        assert_eq!(parallel, sequential, "n = {n}");
+    }
+}
+
+#[test]
+fn sort_parallel_single_thread_is_sequential() {
+    let base: Vec<Record> = (0..300_000usize)
+        .map(|index| Record {
+            key: (index as u32).wrapping_mul(2_654_435_761) % 1_000,
+            index,
+        })
+        .collect();
+    let mut sequential = base.clone();
+    wsort::sort_unstable(&mut sequential);
+    let cfg = wsort::SortConfig {
+        max_threads: 1,
+        parallel_threshold: 0,
+        ..Default::default()
+    };
+    let mut parallel = base.clone();
+    wsort::sort_parallel_with_config(&mut parallel, &cfg);
+    let indices = |v: &[Record]| v.iter().map(|r| r.index).collect::<Vec<_>>();
+    assert_eq!(indices(&parallel), indices(&sequential));
+
+    for max_threads in [2, 3, 8] {
+        let cfg = wsort::SortConfig {
+            max_threads,
+            parallel_threshold: 1_000,
+            ..Default::default()
+        };
+        let mut parallel = base.clone();
+        wsort::sort_parallel_with_config(&mut parallel, &cfg);
+        assert!(wsort::is_sorted(&parallel), "max_threads = {max_threads}");
+    }
+}
+
+#[test]
+fn sort_parallel_never_splits_below_the_threshold() {
+    use std::cmp::Ordering;
+    use std::sync::OnceLock;
+    use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+    use std::thread::{self, ThreadId};
+
+    static CALLER: OnceLock<ThreadId> = OnceLock::new();
+    static OTHER_THREAD: AtomicBool = AtomicBool::new(false);
+
+    /// Records whether it is ever compared off the calling thread.
+    #[derive(PartialEq, Eq)]
+    struct Tracked(i32);
+
+    impl PartialOrd for Tracked {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Tracked {
+        fn cmp(&self, other: &Self) -> Ordering {
+            if CALLER.get() != Some(&thread::current().id()) {
+                OTHER_THREAD.store(true, AtomicOrdering::Relaxed);
+            }
+            self.0.cmp(&other.0)
+        }
+    }
+
+    CALLER.set(thread::current().id()).unwrap();
+    let cfg = wsort::SortConfig {
+        parallel_threshold: 1_000,
+        max_threads: 4,
+        ..Default::default()
+    };
+    let values = |n: i32| (0..n).map(|i| Tracked((i * 7919) % 2_003)).collect();
+
+    // Halves of 999 and 1000 elements: too short to hand to another thread
+    let mut v: Vec<Tracked> = values(1_999);
+    wsort::sort_parallel_with_config(&mut v, &cfg);
+    assert!(wsort::is_sorted(&v));
+    assert!(!OTHER_THREAD.load(AtomicOrdering::Relaxed));
+
+    // Two halves of 1000: split
+    let mut v: Vec<Tracked> = values(2_000);
+    wsort::sort_parallel_with_config(&mut v, &cfg);
+    assert!(wsort::is_sorted(&v));
+    assert!(OTHER_THREAD.load(AtomicOrdering::Relaxed));
+}
+
+#[test]
+fn wave_sort_builder_combines_options() {
+    let base: Vec<i32> = (0..50_000).map(|i| (i * 7919) % 50_021 - 25_000).collect();
+    let mut ascending = base.clone();
+    ascending.sort();
+    let mut descending = ascending.clone();
+    descending.reverse();
+
+    let mut v = base.clone();
+    wsort::WaveSort::new().run(&mut v);
+    assert_eq!(v, ascending);
+
+    let mut v = base.clone();
+    wsort::WaveSort::new()
+        .threshold(2)
+        .descending(true)
+        .run(&mut v);
+    assert_eq!(v, descending);
+
+    // A low parallel threshold forces splits, so the pieces are merged too
+    let split = wsort::SortConfig {
+        parallel_threshold: 1_000,
+        max_threads: 4,
+        ..Default::default()
+    };
+    for (desc, expected) in [(false, &ascending), (true, &descending)] {
+        let mut v = base.clone();
+        wsort::WaveSort::with_config(split.clone())
+            .threshold(48)
+            .descending(desc)
+            .parallel(true)
+            .run(&mut v);
+        assert_eq!(&v, expected, "descending = {desc}");
+    }
+
+    let builder = wsort::WaveSort::new().threshold(48).parallel(false);
+    assert_eq!(builder.config().insertion_threshold, 48);
+}
+
+#[test]
+fn sorted_leaves_input_unchanged() {
+    let input = [5, -1, 3, 3, 0];
+    assert_eq!(wsort::sorted(&input), [-1, 0, 3, 3, 5]);
+    assert_eq!(input, [5, -1, 3, 3, 0]);
+
+    let words = ["pear", "apple", "fig"];
+    assert_eq!(wsort::sorted_vec(&words), ["apple", "fig", "pear"]);
+    assert_eq!(words, ["pear", "apple", "fig"]);
+}
+
+#[test]
+fn is_sorted_and_is_sorted_by() {
+    assert!(wsort::is_sorted::<i32>(&[]));
+    assert!(wsort::is_sorted(&[1, 1, 2]));
+    assert!(!wsort::is_sorted(&[2, 1]));
+    assert!(wsort::is_sorted_by(&[3, 2, 2, 1], |a: &i32, b| b.cmp(a)));
+    assert!(!wsort::is_sorted_by(&[1, 2], |a: &i32, b| b.cmp(a)));
+}
+
+#[test]
+fn is_sorted_until_reports_the_sorted_prefix() {
+    assert_eq!(wsort::is_sorted_until::<i32>(&[]), 0);
+    assert_eq!(wsort::is_sorted_until(&[7]), 1);
+    assert_eq!(wsort::is_sorted_until(&[1, 1, 2, 5]), 4);
+    assert_eq!(wsort::is_sorted_until(&[1, 3, 2]), 2);
+    assert_eq!(wsort::is_sorted_until(&[2, 1]), 1);
+}
+
+#[test]
+fn presorted_inputs() {
+    let mut asc: Vec<i32> = (0..1_000).collect();
+    wsort::sort_unstable(&mut asc);
+    assert!(wsort::is_sorted(&asc));
+
+    let mut desc: Vec<i32> = (0..1_000).rev().collect();
+    wsort::sort_unstable(&mut desc);
+    assert_eq!(desc, (0..1_000).collect::<Vec<_>>());
+
+    let mut plateau = vec![3, 3, 2, 2, 1, 1];
+    wsort::sort_unstable(&mut plateau);
+    assert_eq!(plateau, [1, 1, 2, 2, 3, 3]);
+}
+
+#[test]
+fn partial_sort_prefix() {
+    let base: Vec<i32> = (0..5_000).map(|i| (i * 7919) % 5_003 - 2_500).collect();
+    let mut full = base.clone();
+    full.sort();
+    for k in [0, 1, 10, 2_500, 4_999, 5_000, 9_999] {
+        let mut v = base.clone();
+        wsort::partial_sort(&mut v, k);
+        let k = k.min(v.len());
+        assert_eq!(v[..k], full[..k], "k = {k}");
+        let mut rest = v[k..].to_vec();
+        rest.sort();
+        assert_eq!(rest, full[k..], "k = {k}");
+    }
+}
+
+#[test]
+fn select_nth_matches_sorted_reference() {
+    let base: Vec<i32> = (0..3_001).map(|i| (i * 7919) % 101).collect();
+    let mut reference = base.clone();
+    reference.sort();
+    for n in [0, 1, 100, 1_500, 2_999, 3_000] {
+        let mut v = base.clone();
+        let value = wsort::select_nth(&mut v, n);
+        assert_eq!(value, reference[n], "n = {n}");
+        assert!(v[..n].iter().all(|&x| x <= value));
+        assert!(v[n + 1..].iter().all(|&x| x >= value));
+    }
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn select_nth_out_of_range_panics() {
+    wsort::select_nth(&mut [1, 2, 3], 3);
+}
+
+#[test]
+fn nth_smallest_matches_sorted_reference() {
+    let base: Vec<i32> = (0..1_001).map(|i| (i * 7919) % 211 - 100).collect();
+    let mut reference = base.clone();
+    reference.sort();
+    for k in [0, 1, 500, 1_000] {
+        assert_eq!(wsort::nth_smallest(&mut base.clone(), k), reference[k]);
+    }
+}
+
+#[test]
+#[should_panic(expected = "nth_smallest index 0 out of range for slice of length 0")]
+fn nth_smallest_out_of_range_panics() {
+    wsort::nth_smallest(&mut [], 0);
+}
+
+#[test]
+fn min_max_scans_pairs() {
+    assert_eq!(wsort::min_max(&[]), None);
+    assert_eq!(wsort::min_max(&[7]), Some((7, 7)));
+    assert_eq!(wsort::min_max(&[2, 1]), Some((1, 2)));
+    assert_eq!(
+        wsort::min_max(&[3, i32::MIN, 9, i32::MAX, 0]),
+        Some((i32::MIN, i32::MAX))
+    );
+    for n in 1..40 {
+        let v: Vec<i32> = (0..n).map(|i| (i * 7919) % 37 - 18).collect();
+        let expected = (*v.iter().min().unwrap(), *v.iter().max().unwrap());
+        assert_eq!(wsort::min_max(&v), Some(expected), "n = {n}");
+    }
+}
+
+#[test]
+fn merge_sorted_runs() {
+    let mut v = vec![1, 4, 9, 10, 11, 12, 0, 2, 3];
+    wsort::merge_sorted(&mut v, 6);
+    assert_eq!(v, [0, 1, 2, 3, 4, 9, 10, 11, 12]);
+
+    let mut v = vec![5, 0, 1, 2, 3, 5, 8];
+    wsort::merge_sorted(&mut v, 1);
+    assert_eq!(v, [0, 1, 2, 3, 5, 5, 8]);
+
+    let mut left_only = vec![1, 2, 3];
+    wsort::merge_sorted(&mut left_only, 3);
+    assert_eq!(left_only, [1, 2, 3]);
+
+    let mut right_only = vec![1, 2, 3];
+    wsort::merge_sorted(&mut right_only, 0);
+    assert_eq!(right_only, [1, 2, 3]);
+}
+
+#[test]
+fn sort_instrumented_counts_work() {
+    let mut v: Vec<i32> = (0..10_000).map(|i| (i * 7919) % 10_007).collect();
+    let mut expected = v.clone();
+    expected.sort();
+    let stats = wsort::sort_instrumented(&mut v);
+    assert_eq!(v, expected);
+    assert!(stats.comparisons > 0);
+    assert!(stats.swaps > 0);
+    assert!(stats.max_depth > 0);
+
+    let mut sorted = expected.clone();
+    let stats = wsort::sort_instrumented(&mut sorted);
+    assert_eq!(stats.comparisons, sorted.len() as u64 - 1);
+    assert_eq!(stats.swaps, 0);
+}
+
+/// Ordered by `key` only, so records with equal keys compare equal.
+#[derive(Debug, Clone, Copy)]
+struct Record {
+    key: u32,
+    index: usize,
+}
+
+impl PartialEq for Record {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Record {}
+
+impl PartialOrd for Record {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Record {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+#[test]
+fn sort_keeps_equal_keys_in_order() {
+    #[allow(deprecated)]
+    let sorts: [fn(&mut [Record]); 4] = [wsort::sort, wsort::sort_stable, wsort::merge_sort, |v| {
+        let cfg = wsort::SortConfig {
+            backend: wsort::Backend::MergeSort,
+            ..Default::default()
+        };
+        wsort::sort_with_config(v, &cfg)
+    }];
+    for sort in sorts {
+        for n in [0, 1, 31, 32, 33, 100, 5_000] {
+            let mut v: Vec<Record> = (0..n)
+                .map(|index| Record {
+                    key: ((index * 7919) % 13) as u32,
+                    index,
+                })
+                .collect();
+            sort(&mut v);
+            for w in v.windows(2) {
+                assert!(w[0].key <= w[1].key, "n = {n}");
+                if w[0].key == w[1].key {
+                    assert!(w[0].index < w[1].index, "n = {n}: {w:?}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn merge_backend_matches_std_stable_sort() {
+    // Uneven lengths make the last merge of each pass lopsided, so both the
+    // forward (left run buffered) and backward (right run buffered) merges run
+    for n in [2, 3, 33, 100, 1_000, 4_099] {
+        for shape in 0..3 {
+            let base: Vec<Record> = (0..n)
+                .map(|index| Record {
+                    key: match shape {
+                        0 => ((index * 7919) % 17) as u32,
+                        1 => (index / 3) as u32,
+                        _ => ((n - index) / 5) as u32,
+                    },
+                    index,
+                })
+                .collect();
+            let mut expected = base.clone();
+            expected.sort();
+            for insertion_threshold in [1, 2, 32] {
+                let cfg = wsort::SortConfig {
+                    insertion_threshold,
+                    backend: wsort::Backend::MergeSort,
+                    ..Default::default()
+                };
+                let mut v = base.clone();
+                wsort::sort_with_config(&mut v, &cfg);
+                let indices = |v: &[Record]| v.iter().map(|r| r.index).collect::<Vec<_>>();
+                assert_eq!(
+                    indices(&v),
+                    indices(&expected),
+                    "n = {n}, shape = {shape}, threshold = {insertion_threshold}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn tiny_slices_sort_exhaustively() {
+    // Slices of up to 16 elements are finished by sorting networks, for any
+    // comparator. By the 0-1 principle a network that sorts every 0/1 input
+    // sorts everything.
+    for n in 0..=17 {
+        for bits in 0u32..1 << n {
+            let mut v: Vec<u32> = (0..n).map(|i| (bits >> i) & 1).collect();
+            let mut expected = v.clone();
+            expected.sort();
+            let mut desc = v.clone();
+            wsort::sort_unstable(&mut v);
+            assert_eq!(v, expected, "n = {n}, bits = {bits:b}");
+            wsort::sort_by(&mut desc, |a, b| b.cmp(a));
+            expected.reverse();
+            assert_eq!(desc, expected, "desc: n = {n}, bits = {bits:b}");
+        }
+    }
+    let mut v = vec![String::from("d"), "b".into(), "a".into(), "c".into()];
+    wsort::sort_unstable(&mut v);
+    assert_eq!(v, ["a", "b", "c", "d"]);
+}
+
+#[test]
+fn sort_i32_networks_sort_every_small_length() {
+    // `sort_i32` finishes up to 16 elements with a sorting network; by the
+    // 0-1 principle checking every 0/1 input of each length suffices.
+    for n in 0..=16 {
+        for bits in 0u32..1 << n {
+            let mut v: Vec<i32> = (0..n).map(|i| ((bits >> i) & 1) as i32).collect();
+            let mut expected = v.clone();
+            expected.sort();
+            wsort::sort_i32(&mut v);
+            assert_eq!(v, expected, "n = {n}, bits = {bits:b}");
+        }
+    }
+    let mut seed: u64 = 5;
+    for n in 0..=16 {
+        for _ in 0..200 {
+            let mut v: Vec<i32> = (0..n)
+                .map(|_| {
+                    seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    (seed >> 32) as i32
+                })
+                .collect();
+            let mut expected = v.clone();
+            expected.sort();
+            wsort::sort_i32(&mut v);
+            assert_eq!(v, expected, "n = {n}");
+        }
+    }
+}
+
+#[test]
+fn every_entry_point_accepts_empty_and_single_element_slices() {
+    // Every thread split and partition is forced with the lowest thresholds.
+    let eager = wsort::SortConfig {
+        insertion_threshold: 0,
+        parallel_threshold: 0,
+        max_threads: 4,
+        ..Default::default()
+    };
+    type SortFn = fn(&mut [i32]);
+    let entries: Vec<(&str, SortFn)> = vec![
+        ("sort", wsort::sort),
+        ("sort_unstable", wsort::sort_unstable),
+        ("sort_recursive", wsort::sort_recursive),
+        ("merge_sort", wsort::merge_sort),
+        ("sort_i32", wsort::sort_i32),
+        ("sort_radix", wsort::sort_radix),
+        ("sort_counting", |v| wsort::sort_counting(v, -10, 10)),
+        ("sort_parallel", wsort::sort_parallel),
+        ("sort_asm", wsort::sort_asm),
+        ("wavesort_asm_safe", wsort::wavesort_asm_safe),
+        ("wavesort_asm_safe_desc", wsort::wavesort_asm_safe_desc),
+        ("heapsort", wsort::heapsort),
+        ("sort_desc", wsort::sort_desc),
+        ("partial_sort(0)", |v| wsort::partial_sort(v, 0)),
+        ("partial_sort(1)", |v| wsort::partial_sort(v, 1)),
+        ("sort_range", |v| wsort::sort_range(v, 0..v.len())),
+        ("wave_arrange", wsort::wave_arrange),
+    ];
+    for len in 0..=1 {
+        let input: Vec<i32> = vec![7; len];
+        for &(name, sort) in &entries {
+            let mut v = input.clone();
+            sort(&mut v);
+            assert_eq!(v, input, "{name}, len = {len}");
+        }
+        for tier in [
+            wsort::AsmTier::Scalar,
+            wsort::AsmTier::Sse2,
+            wsort::AsmTier::Avx2,
+        ] {
+            if tier.is_supported() {
+                let mut v = input.clone();
+                wsort::wavesort_asm_tier(&mut v, tier);
+                assert_eq!(v, input, "{tier:?}, len = {len}");
+            }
+        }
+        let mut v = input.clone();
+        wsort::sort_with_config(&mut v, &eager);
+        wsort::sort_parallel_with_config(&mut v, &eager);
+        wsort::WaveSort::with_config(eager.clone())
+            .parallel(true)
+            .run(&mut v);
+        assert_eq!(v, input, "WaveSort, len = {len}");
+        assert_eq!(wsort::sort_cancellable(&mut v, &Default::default()), Ok(()));
+    }
+
+    let mut single = [42];
+    assert_eq!(wsort::select_nth(&mut single, 0), 42);
+    assert_eq!(wsort::nth_smallest(&mut single, 0), 42);
+    assert_eq!(single, [42]);
+}
+
+#[test]
+fn wave_boundaries_small_thresholds() {
+    // Tiny thresholds push upwave/downwave through their expansion and split
+    // boundaries on short ranges; debug builds also check the index
+    // invariants on every call.
+    for threshold in [0, 1, 2, 3, 5] {
+        let cfg = wsort::SortConfig {
+            insertion_threshold: threshold,
+            ..Default::default()
+        };
+        for n in 0..300usize {
+            for shape in 0..3 {
+                let mut v: Vec<usize> = match shape {
+                    0 => (0..n).map(|i| (i * 7919) % 101).collect(),
+                    1 => (0..n).map(|i| if i % 5 == 0 { n - i } else { i }).collect(),
+                    _ => (0..n).map(|i| i % 3).collect(),
+                };
+                let mut expected = v.clone();
+                expected.sort();
+                wsort::sort_with_config(&mut v, &cfg);
+                assert_eq!(v, expected, "threshold {threshold}, n {n}, shape {shape}");
+            }
+        }
+    }
+}
+
+#[test]
+fn rotate_merge_matches_naive_merge() {
+    let mut seed: u64 = 3;
+    let mut next = move |m: u64| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((seed >> 33) % m) as i32
+    };
+    for _ in 0..200 {
+        let left_len = next(60) as usize;
+        let right_len = next(60) as usize;
+        let tail = next(5) as usize;
+        let mut left: Vec<i32> = (0..left_len).map(|_| next(40)).collect();
+        let mut right: Vec<i32> = (0..right_len).map(|_| next(40)).collect();
+        left.sort();
+        right.sort();
+
+        let (mut i, mut j) = (0, 0);
+        let mut expected = Vec::with_capacity(left_len + right_len);
+        while i < left.len() && j < right.len() {
+            if right[j] < left[i] {
+                expected.push(right[j]);
+                j += 1;
+            } else {
+                expected.push(left[i]);
+                i += 1;
+            }
+        }
+        expected.extend_from_slice(&left[i..]);
+        expected.extend_from_slice(&right[j..]);
+        expected.extend(std::iter::repeat_n(-1, tail));
+
+        let mut v = left;
+        v.extend_from_slice(&right);
+        v.extend(std::iter::repeat_n(-1, tail));
+        wsort::rotate_merge(&mut v, left_len, left_len + right_len);
+        assert_eq!(v, expected);
+    }
+}
+
+#[test]
+fn sort_i32_matches_sort() {
+    let mut seed: u64 = 11;
+    for n in (0..=40).chain([100, 1_000, 50_000]) {
+        for modulus in [3u64, 1 << 32] {
+            let mut v: Vec<i32> = (0..n)
+                .map(|_| {
+                    seed = seed
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    ((seed >> 16) % modulus) as u32 as i32
+                })
+                .collect();
+            if n > 2 {
+                v[0] = i32::MAX;
+                v[n - 1] = i32::MIN;
+            }
+            let mut expected = v.clone();
+            expected.sort();
+            wsort::sort_i32(&mut v);
+            assert_eq!(v, expected, "n = {n}");
+        }
+    }
+}
+
+#[test]
+fn embedded_natural_runs() {
+    let n: usize = 20_000;
+    let shapes: [Vec<i32>; 4] = [
+        // Long ascending runs with overlapping value ranges
+        (0..n)
+            .map(|i| ((i % 5_000) * 4 + i / 5_000) as i32)
+            .collect(),
+        // Alternating ascending and descending runs
+        (0..n)
+            .map(|i| {
+                let (run, off) = (i / 3_000, i % 3_000);
+                (if run % 2 == 0 { off } else { 3_000 - off } * 7 + run) as i32
+            })
+            .collect(),
+        // A sorted prefix followed by a descending tail
+        (0..n)
+            .map(|i| {
+                if i < n / 2 {
+                    i as i32
+                } else {
+                    (2 * n - i) as i32 / 3
+                }
+            })
+            .collect(),
+        // A sorted array with short random blocks spliced in
+        (0..n)
+            .map(|i| {
+                if i % 4_000 < 50 {
+                    ((i * 7919) % n) as i32
+                } else {
+                    i as i32
+                }
+            })
+            .collect(),
+    ];
+    for (shape, input) in shapes.into_iter().enumerate() {
+        let mut expected = input.clone();
+        expected.sort();
+        let mut v = input.clone();
+        wsort::sort_unstable(&mut v);
+        assert_eq!(v, expected, "shape {shape}");
+        let mut v = input;
+        wsort::sort_desc(&mut v);
+        expected.reverse();
+        assert_eq!(v, expected, "shape {shape} descending");
+    }
+}
+
+#[test]
+fn sort_dedup_keeps_distinct_values() {
+    let mut v = vec![3, 1, 2, 1, 3];
+    assert_eq!(wsort::sort_dedup(&mut v), 3);
+    assert_eq!(v, [1, 2, 3]);
+
+    let mut v = Vec::new();
+    assert_eq!(wsort::sort_dedup(&mut v), 0);
+
+    let mut v = vec![7; 100];
+    assert_eq!(wsort::sort_dedup(&mut v), 1);
+    assert_eq!(v, [7]);
+}
+
+#[test]
+fn sort_run_lengths_counts_values() {
+    let mut v = [4, -2, 4, 0, -2, 4];
+    assert_eq!(wsort::sort_run_lengths(&mut v), [(-2, 2), (0, 1), (4, 3)]);
+    assert_eq!(v, [-2, -2, 0, 4, 4, 4]);
+
+    assert!(wsort::sort_run_lengths(&mut []).is_empty());
+
+    let mut v = [9; 50];
+    assert_eq!(wsort::sort_run_lengths(&mut v), [(9, 50)]);
+}
+
+#[test]
+fn wave_arrange_alternates() {
+    for n in [0, 1, 2, 3, 4, 5, 100, 1_001] {
+        let mut v: Vec<i32> = (0..n).map(|i| (i * 7919) % 97 - 40).collect();
+        let mut expected = v.clone();
+        expected.sort();
+        wsort::wave_arrange(&mut v);
+        for (i, w) in v.windows(2).enumerate() {
+            if i % 2 == 0 {
+                assert!(w[0] <= w[1], "n = {n}, i = {i}: {v:?}");
+            } else {
+                assert!(w[0] >= w[1], "n = {n}, i = {i}: {v:?}");
+            }
+        }
+        v.sort();
+        assert_eq!(v, expected);
+    }
+}
+
+#[test]
+fn heapsort_matches_std() {
+    for n in [0, 1, 2, 3, 100, 10_000] {
+        let random: Vec<i32> = (0..n as i64)
+            .map(|i| (i * 2_654_435_761 % 4_294_967_291 - 2_147_483_645) as i32)
+            .collect();
+        let mut sorted = random.clone();
+        sorted.sort();
+        for input in [random, vec![7; n], sorted.clone()] {
+            let mut expected = input.clone();
+            expected.sort();
+            let mut v = input;
+            wsort::heapsort(&mut v);
+            assert_eq!(v, expected, "n = {n}");
+        }
+    }
+}
+
+#[test]
+fn sort_radix_matches_std() {
+    for n in [0, 1, 2, 255, 256, 257, 10_000] {
+        let mut v: Vec<i32> = (0..n as i64)
+            .map(|i| (i * 2_654_435_761 % 4_294_967_291 - 2_147_483_645) as i32)
+            .collect();
+        v.extend([i32::MIN, i32::MAX, 0, -1]);
+        let mut expected = v.clone();
+        expected.sort();
+        wsort::sort_radix(&mut v);
+        assert_eq!(v, expected, "n = {n}");
+    }
+    // Keys that share their upper bytes skip those passes
+    let mut v: Vec<i32> = (0..1_000).rev().collect();
+    wsort::sort_radix(&mut v);
+    assert!(wsort::is_sorted(&v));
+}
+
+#[test]
+fn sort_i32_with_config_radix_threshold() {
+    let base: Vec<i32> = (0..5_000).map(|i| (i * 7919) % 4_099 - 2_000).collect();
+    let mut expected = base.clone();
+    expected.sort();
+    for radix_threshold in [None, Some(0), Some(1_000), Some(10_000)] {
+        let mut v = base.clone();
+        let cfg = wsort::SortConfig {
+            radix_threshold,
+            ..Default::default()
+        };
+        wsort::sort_i32_with_config(&mut v, &cfg);
+        assert_eq!(v, expected, "radix_threshold = {radix_threshold:?}");
+    }
+}
+
+#[test]
+fn wave_sorter_sorts_many_slices() {
+    let mut sorter = wsort::WaveSorter::with_config(wsort::SortConfig {
+        radix_threshold: Some(1_000),
+        ..Default::default()
+    });
+    for (round, n) in [0usize, 1, 10, 500, 3_000, 2_000, 20_000, 100]
+        .into_iter()
+        .enumerate()
+    {
+        // Alternate wide and narrow ranges to exercise every path
+        let modulus = if round % 2 == 0 { 1_000_003 } else { 7 };
+        let mut v: Vec<i32> = (0..n as i64)
+            .map(|i| (i * 2_654_435_761 % modulus) as i32 - 3)
+            .collect();
+        let mut expected = v.clone();
+        expected.sort();
+        sorter.sort(&mut v);
+        assert_eq!(v, expected, "n = {n}");
+    }
+    assert!(sorter.scratch_capacity() >= 20_000);
+
+    let mut in_place = wsort::WaveSorter::new();
+    let mut v: Vec<i32> = (0..5_000).map(|i| (i * 7919) % 1_000_003).collect();
+    in_place.sort(&mut v);
+    assert!(wsort::is_sorted(&v));
+    assert_eq!(in_place.scratch_capacity(), 0);
+}
+
+#[test]
+fn sort_counting_small_ranges() {
+    let mut v = [5; 40];
+    wsort::sort_counting(&mut v, 5, 5);
+    assert_eq!(v, [5; 40]);
+
+    let mut v = [3, -4, 0, -4, 2, -1, 3];
+    wsort::sort_counting(&mut v, -4, 3);
+    assert_eq!(v, [-4, -4, -1, 0, 2, 3, 3]);
+
+    // Too wide to count: falls back to WaveSort
+    let mut v = [i32::MAX, 0, i32::MIN];
+    wsort::sort_counting(&mut v, i32::MIN, i32::MAX);
+    assert_eq!(v, [i32::MIN, 0, i32::MAX]);
+}
+
+#[test]
+#[should_panic(expected = "outside")]
+fn sort_counting_rejects_out_of_range() {
+    wsort::sort_counting(&mut [1, 9], 0, 5);
+}
+
+#[test]
+fn sort_i32_detects_narrow_ranges() {
+    for (lo, span) in [(-500, 1), (-500, 100), (i32::MAX - 99, 100), (0, 5_000)] {
+        let base: Vec<i32> = (0..2_000i64)
+            .map(|i| (lo as i64 + (i * 7919) % span) as i32)
+            .collect();
+        let mut expected = base.clone();
+        expected.sort();
+        let mut v = base;
+        wsort::sort_i32(&mut v);
+        assert_eq!(v, expected, "lo = {lo}, span = {span}");
+    }
+}
+
+#[test]
+fn sort_bytes_matches_std() {
+    let mut seed: u32 = 12345;
+    for n in [0, 1, 2, 255, 256, 10_000] {
+        let mut v: Vec<u8> = (0..n)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (seed >> 16) as u8
+            })
+            .collect();
+        let mut expected = v.clone();
+        expected.sort();
+        wsort::sort_bytes(&mut v);
+        assert_eq!(v, expected, "n = {n}");
+    }
+    let mut text = *b"the quick brown fox";
+    wsort::sort_bytes(&mut text);
+    assert_eq!(&text, b"   bcefhiknooqrtuwx");
+}
+
+#[test]
+fn sort_bools_puts_false_first() {
+    let mut v = [true, false, true, true, false, false, true, false, false];
+    wsort::sort_bools(&mut v);
+    assert_eq!(
+        v,
+        [false, false, false, false, false, true, true, true, true]
+    );
+    for n in [0, 1, 2, 1_000] {
+        let mut v: Vec<bool> = (0..n).map(|i| i * 7919 % 3 == 0).collect();
+        let mut expected = v.clone();
+        expected.sort();
+        wsort::sort_bools(&mut v);
+        assert_eq!(v, expected, "n = {n}");
+    }
+}
+
+#[test]
+fn partition_around_duplicate_pivots() {
+    let inputs: [&[i32]; 5] = [
+        &[5],
+        &[3, 3, 3, 3],
+        &[4, 1, 4, 9, 4, 0, 4, 7],
+        &[9, 8, 7, 5, 5, 5, 1, 2],
+        &[1, 5, 2, 5, 3, 5, 4, 5, 5],
+    ];
+    for input in inputs {
+        for pivot_index in 0..input.len() {
+            let mut v = input.to_vec();
+            let pivot = v[pivot_index];
+            let m = wsort::partition(&mut v, pivot_index);
+            assert_eq!(v[m], pivot, "{input:?} @ {pivot_index}");
+            assert!(
+                v[..m].iter().all(|&x| x <= pivot),
+                "{input:?} @ {pivot_index}"
+            );
+            assert!(
+                v[m..].iter().all(|&x| x >= pivot),
+                "{input:?} @ {pivot_index}"
+            );
+            let less = input.iter().filter(|&&x| x < pivot).count();
+            let not_greater = input.iter().filter(|&&x| x <= pivot).count();
+            assert!(
+                (less..not_greater).contains(&m),
+                "{input:?} @ {pivot_index}"
+            );
+            v.sort();
+            let mut expected = input.to_vec();
+            expected.sort();
+            assert_eq!(v, expected);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn partition_rejects_bad_pivot() {
+    wsort::partition(&mut [1, 2, 3], 3);
+}
+
+#[test]
+fn kmerge_three_sorted_sources() {
+    let mut a = [1, 4, 7, 10].into_iter();
+    let mut b = (2..9).step_by(3);
+    let mut c = vec![0, 4, 4, 11].into_iter();
+    let mut sources: [&mut dyn Iterator<Item = i32>; 3] = [&mut a, &mut b, &mut c];
+    let merged: Vec<i32> = wsort::kmerge(&mut sources).collect();
+    assert_eq!(merged, [0, 1, 2, 4, 4, 4, 5, 7, 8, 10, 11]);
+
+    let mut empty = std::iter::empty();
+    let mut sources: [&mut dyn Iterator<Item = i32>; 1] = [&mut empty];
+    assert_eq!(wsort::kmerge(&mut sources).count(), 0);
+    assert_eq!(wsort::kmerge(&mut []).count(), 0);
+}
+
+#[test]
+fn sort_copy_baseline_copies_without_sorting() {
+    let src = [3, -1, 2];
+    let mut dst = [0; 3];
+    wsort::sort_copy_baseline(&src, &mut dst);
+    assert_eq!(dst, src);
+}
+
+#[test]
+#[should_panic]
+fn sort_copy_baseline_rejects_mismatched_lengths() {
+    wsort::sort_copy_baseline(&[1, 2], &mut [0; 3]);
+}
+
+#[test]
+fn sort_with_positions_orders_equal_values_by_index() {
+    assert_eq!(wsort::sort_with_positions(&[]), []);
+    let arr: Vec<i32> = (0..5_000)
+        .map(|i| [i32::MIN, -7, -1, 0, 1, 7, i32::MAX][(i * 7919) % 7])
+        .collect();
+    let pairs = wsort::sort_with_positions(&arr);
+    let mut expected: Vec<(i32, usize)> = arr.iter().copied().zip(0..).collect();
+    expected.sort();
+    assert_eq!(pairs, expected);
+    for &(v, i) in &pairs {
+        assert_eq!(arr[i], v);
+    }
+}
+
+#[test]
+fn sort_indices_orders_lookups() {
+    let arr: Vec<i32> = (0..2_000).map(|i| (i * 7919) % 257 - 128).collect();
+    let indices = wsort::sort_indices(&arr);
+    let looked_up: Vec<i32> = indices.iter().map(|&i| arr[i]).collect();
+    assert!(wsort::is_sorted(&looked_up));
+    let mut seen = indices.clone();
+    seen.sort();
+    assert!(seen.iter().copied().eq(0..arr.len()));
+    // Ties keep input order
+    assert_eq!(wsort::sort_indices(&[2, 1, 2, 1]), [1, 3, 0, 2]);
+    assert!(wsort::sort_indices::<i32>(&[]).is_empty());
+}
+
+#[test]
+fn apply_permutation_reproduces_sort() {
+    for n in [0, 1, 2, 100, 5_000] {
+        let mut arr: Vec<i64> = (0..n as i64).map(|i| (i * 7919) % 1_009 - 500).collect();
+        let mut expected = arr.clone();
+        expected.sort();
+        let perm = wsort::sort_indices(&arr);
+        wsort::apply_permutation(&mut arr, &perm);
+        assert_eq!(arr, expected, "n = {n}");
+    }
+    // A single long cycle
+    let mut arr = ['b', 'c', 'd', 'a'];
+    wsort::apply_permutation(&mut arr, &[1, 2, 3, 0]);
+    assert_eq!(arr, ['c', 'd', 'a', 'b']);
+}
+
+// Only checked by a debug assertion
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "not a permutation")]
+fn apply_permutation_rejects_repeats() {
+    wsort::apply_permutation(&mut [1, 2, 3], &[1, 1, 0]);
+}
+
+#[test]
+fn sort_by_cached_key_calls_key_once_per_element() {
+    for n in [0, 1, 2, 33, 1_000] {
+        let mut v: Vec<String> = (0..n).map(|i| ((i * 7919) % 101).to_string()).collect();
+        let mut expected = v.clone();
+        expected.sort_by_key(|s| s.parse::<u32>().unwrap());
+        let mut calls = 0;
+        wsort::sort_by_cached_key(&mut v, |s| {
+            calls += 1;
+            s.parse::<u32>().unwrap()
+        });
+        assert_eq!(calls, n, "n = {n}");
+        // std's sort_by_key is stable too, so equal keys line up exactly
+        assert_eq!(v, expected, "n = {n}");
+    }
+}
+
+#[test]
+fn sort_range_sorts_only_the_range() {
+    let mut v = [9, 8, 7, 6, 5, 4, 3];
+    wsort::sort_range(&mut v, 2..5);
+    assert_eq!(v, [9, 8, 5, 6, 7, 4, 3]);
+
+    wsort::sort_range(&mut v, 3..3);
+    wsort::sort_range(&mut v, 7..7);
+    assert_eq!(v, [9, 8, 5, 6, 7, 4, 3]);
+
+    wsort::sort_range(&mut v, 0..7);
+    assert_eq!(v, [3, 4, 5, 6, 7, 8, 9]);
+}
+
+#[test]
+#[should_panic(expected = "sort_range 2..8 out of bounds for slice of length 7")]
+fn sort_range_rejects_out_of_bounds() {
+    wsort::sort_range(&mut [0; 7], 2..8);
+}
+
+#[test]
+fn sort_cancellable_stops_when_cancelled() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, Instant};
+
+    let input: Vec<i32> = (0..1_000_000i64)
+        .map(|i| (i * 2_654_435_761 % 4_294_967_291) as i32)
+        .collect();
+    let mut expected = input.clone();
+    expected.sort();
+
+    // Raised up front: nothing is sorted
+    let mut v = input.clone();
+    let cancel = AtomicBool::new(true);
+    assert_eq!(
+        wsort::sort_cancellable(&mut v, &cancel),
+        Err(wsort::Cancelled)
+    );
+    assert_eq!(v, input);
+
+    // Raised mid-sort: the sort returns early with a permutation
+    let mut v = input.clone();
+    let cancel = AtomicBool::new(false);
+    let (result, latency) = std::thread::scope(|s| {
+        let raiser = s.spawn(|| {
+            std::thread::sleep(Duration::from_millis(5));
+            cancel.store(true, Ordering::Relaxed);
+            Instant::now()
+        });
+        let result = wsort::sort_cancellable(&mut v, &cancel);
+        let returned = Instant::now();
+        (result, returned - raiser.join().unwrap())
+    });
+    assert_eq!(result, Err(wsort::Cancelled));
+    assert!(latency < Duration::from_secs(1), "took {latency:?} to stop");
+    assert!(!wsort::is_sorted(&v));
+    v.sort();
+    assert_eq!(v, expected);
+
+    // Never raised: a full sort
+    let mut v = input;
+    assert_eq!(
+        wsort::sort_cancellable(&mut v, &AtomicBool::new(false)),
+        Ok(())
+    );
+    assert_eq!(v, expected);
+}
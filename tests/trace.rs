@@ -0,0 +1,37 @@
+//! Runs `wsort-cli` built with the `trace` feature and checks that the wave
+//! recursion is logged to stderr.
+//!
+//! Run with `cargo test --features trace --test trace`.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn logs_waves_and_partitions() {
+    // Long enough to go past the insertion sort threshold
+    let input: String = (0..200).map(|i| format!("{} ", (i * 37) % 101)).collect();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_wsort-cli"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    let trace = String::from_utf8(out.stderr).unwrap();
+    assert!(trace.lines().next() == Some("[upwave 0..199]"), "{trace}");
+    assert!(
+        trace.lines().any(|l| l.starts_with("[downwave ")),
+        "{trace}"
+    );
+    assert!(
+        trace.lines().any(|l| l.starts_with("[partition -> ")),
+        "{trace}"
+    );
+}